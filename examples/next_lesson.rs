@@ -2,7 +2,6 @@
 ///
 /// The user is prompted for their username, password and school. Which is then used to authenticate
 /// with Schoolsoft. The user's schedule is then fetched and searched for the next lesson.
-use chrono::{Datelike, Utc};
 use schoolsoft::ClientBuilder;
 use std::io;
 
@@ -15,7 +14,7 @@ async fn main() -> io::Result<()> {
     let password = prompt("Password: ")?;
     let school = prompt("School: ")?;
 
-    match client.login(&username, &password, &school).await {
+    match client.login(&username, password, &school).await {
         Ok(_) => {}
         Err(e) => {
             eprintln!("Failed to login: {}", e);
@@ -24,36 +23,16 @@ async fn main() -> io::Result<()> {
     };
 
     // Get user
-    let mut user = client.user.unwrap();
+    let user = client.user.as_ref().unwrap();
     println!("Logged in as {}", user.name);
 
-    // Get current time
-    let now = Utc::now().naive_local();
-    let today = now.date();
-
     // Get schedule
-    let mut weeks = user.get_schedule().await.unwrap().weeks;
+    let schedule = user.get_schedule().await.unwrap();
 
     // Find the next lesson
-    for day in today.iter_days() {
-        let week = weeks.get_mut(day.iso_week().week0() as usize).unwrap();
-        let schedule = week.get_day(day.weekday());
-        let lessons = &schedule.lessons;
-
-        if lessons.is_empty() {
-            continue;
-        }
-
-        // Find the first lesson that hasn't started yet, if any
-        for lesson in lessons.iter() {
-            // if lesson is in the past, skip it
-            if schedule.date.and_time(lesson.start) < now {
-                continue;
-            }
-
-            dbg!(lesson);
-            return Ok(());
-        }
+    if let Some((day, lesson)) = schedule.next_lesson(client.clock()) {
+        println!("{}", day.date);
+        dbg!(lesson);
     }
 
     Ok(())
@@ -0,0 +1,294 @@
+//! Blocking versions of [`crate::Client`] and [`crate::User`]
+//!
+//! Only available with the `blocking` feature. The types here wrap the async ones and run each
+//! call to completion on a small runtime owned by the client, so they make the same requests and
+//! return the same errors without the caller needing an async runtime of their own.
+//!
+//! Don't use them from inside an async runtime, blocking on a runtime from within another one
+//! panics.
+//!
+//! # Examples
+//! ```no_run
+//! # use schoolsoft::ClientBuilder;
+//! let mut client = ClientBuilder::new().build_blocking();
+//!
+//! client.login("username", "password", "school").unwrap();
+//! let user = client.user.as_ref().unwrap();
+//!
+//! let lunch = user.get_lunch().unwrap();
+//! ```
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use tokio::runtime::Runtime;
+
+use crate::{
+    schedule::Schedule,
+    secret::Secret,
+    session::SessionStore,
+    types::{
+        error::{
+            LoginError, LunchMenuError, ScheduleError, SchoolListingError, SessionError, TokenError,
+        },
+        LunchMenu, Org, SchoolListing, Token, UserType,
+    },
+    ClientBuilder,
+};
+
+#[cfg(feature = "unverified")]
+use crate::{
+    report::{AbsenceReport, ReportOutcome},
+    types::{
+        error::{AbsenceError, ChildrenError, GradesError, NewsError, PlanningError, ReportError},
+        Absence, Child, Grade, NewsItem, PlanningItem,
+    },
+};
+
+/// Blocking version of [`crate::Client`]
+///
+/// Created with [`ClientBuilder::build_blocking`].
+#[derive(Debug)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+
+    /// Some(user) if the client is logged in.
+    /// None if the client is not logged in.
+    pub user: Option<User>,
+}
+
+/// Blocking version of [`crate::User`]
+///
+/// Dereferences to the async user for everything that doesn't make requests, like the name,
+/// orgs, selecting a child or saving the session.
+#[derive(Debug, Clone)]
+pub struct User {
+    inner: crate::User,
+    runtime: Arc<Runtime>,
+}
+
+impl ClientBuilder {
+    /// Build a [`Client`] that blocks instead of returning futures
+    ///
+    /// # Panics
+    /// If the runtime used to run requests can't be created.
+    pub fn build_blocking(self) -> Client {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("should be able to create a runtime for blocking requests");
+
+        Client {
+            inner: self.build(),
+            runtime: Arc::new(runtime),
+            user: None,
+        }
+    }
+}
+
+impl Client {
+    /// Take the user from the async client after logging in or restoring a session
+    fn take_user(&mut self) {
+        self.user = self.inner.user.take().map(|user| User {
+            inner: user,
+            runtime: self.runtime.clone(),
+        });
+    }
+
+    /// See [`crate::Client::login`]
+    pub fn login(
+        &mut self,
+        username: &str,
+        password: impl Into<Secret>,
+        school: &str,
+    ) -> Result<(), LoginError> {
+        self.login_as(username, password, school, UserType::Student)
+    }
+
+    /// See [`crate::Client::login_as`]
+    pub fn login_as(
+        &mut self,
+        username: &str,
+        password: impl Into<Secret>,
+        school: &str,
+        user_type: UserType,
+    ) -> Result<(), LoginError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(self.inner.login_as(username, password, school, user_type))?;
+
+        self.take_user();
+        Ok(())
+    }
+
+    /// See [`crate::Client::login_to`]
+    pub fn login_to(
+        &mut self,
+        school: &SchoolListing,
+        username: &str,
+        password: impl Into<Secret>,
+        user_type: UserType,
+    ) -> Result<(), LoginError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(self.inner.login_to(school, username, password, user_type))?;
+
+        self.take_user();
+        Ok(())
+    }
+
+    /// See [`crate::Client::restore_session`]
+    pub fn restore_session(&mut self, store: &impl SessionStore) -> Result<bool, SessionError> {
+        if !self.inner.restore_session(store)? {
+            return Ok(false);
+        }
+
+        self.take_user();
+        Ok(true)
+    }
+
+    /// See [`crate::Client::schools`]
+    pub fn schools(&self) -> Result<Vec<SchoolListing>, SchoolListingError> {
+        self.runtime.block_on(self.inner.schools())
+    }
+
+    /// See [`crate::Client::adopt`]
+    pub fn adopt(&self, user: crate::User) -> User {
+        User {
+            inner: self.inner.adopt(user),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get the async client, for the settings it was built with
+    pub fn as_async(&self) -> &crate::Client {
+        &self.inner
+    }
+}
+
+impl User {
+    /// See [`crate::User::reauthenticate`]
+    pub fn reauthenticate(&self) -> Result<bool, LoginError> {
+        self.runtime.block_on(self.inner.reauthenticate())
+    }
+
+    /// See [`crate::User::get_children`]
+    #[cfg(feature = "unverified")]
+    pub fn get_children(&self) -> Result<Vec<Child>, ChildrenError> {
+        self.runtime.block_on(self.inner.get_children())
+    }
+
+    /// See [`crate::User::get_token`]
+    pub fn get_token(&self) -> Result<Token, TokenError> {
+        self.runtime.block_on(self.inner.get_token())
+    }
+
+    /// See [`crate::User::smart_token`]
+    pub fn smart_token(&self) -> Result<Token, TokenError> {
+        self.runtime.block_on(self.inner.smart_token())
+    }
+
+    /// See [`crate::User::get_lunch`]
+    pub fn get_lunch(&self) -> Result<LunchMenu, LunchMenuError> {
+        self.runtime.block_on(self.inner.get_lunch())
+    }
+
+    /// See [`crate::User::get_lunch_for`]
+    pub fn get_lunch_for(&self, org: &Org) -> Result<LunchMenu, LunchMenuError> {
+        self.runtime.block_on(self.inner.get_lunch_for(org))
+    }
+
+    /// See [`crate::User::get_planning`]
+    #[cfg(feature = "unverified")]
+    pub fn get_planning(&self) -> Result<Vec<PlanningItem>, PlanningError> {
+        self.runtime.block_on(self.inner.get_planning())
+    }
+
+    /// See [`crate::User::get_planning_for`]
+    #[cfg(feature = "unverified")]
+    pub fn get_planning_for(&self, org: &Org) -> Result<Vec<PlanningItem>, PlanningError> {
+        self.runtime.block_on(self.inner.get_planning_for(org))
+    }
+
+    /// See [`crate::User::get_grades`]
+    #[cfg(feature = "unverified")]
+    pub fn get_grades(&self) -> Result<Vec<Grade>, GradesError> {
+        self.runtime.block_on(self.inner.get_grades())
+    }
+
+    /// See [`crate::User::get_grades_for`]
+    #[cfg(feature = "unverified")]
+    pub fn get_grades_for(&self, org: &Org) -> Result<Vec<Grade>, GradesError> {
+        self.runtime.block_on(self.inner.get_grades_for(org))
+    }
+
+    /// See [`crate::User::get_absences`]
+    #[cfg(feature = "unverified")]
+    pub fn get_absences(&self) -> Result<Vec<Absence>, AbsenceError> {
+        self.runtime.block_on(self.inner.get_absences())
+    }
+
+    /// See [`crate::User::get_absences_for`]
+    #[cfg(feature = "unverified")]
+    pub fn get_absences_for(&self, org: &Org) -> Result<Vec<Absence>, AbsenceError> {
+        self.runtime.block_on(self.inner.get_absences_for(org))
+    }
+
+    /// See [`crate::User::report_absence`]
+    #[cfg(feature = "unverified")]
+    pub fn report_absence(
+        &self,
+        report: &AbsenceReport,
+        schedule: &Schedule,
+    ) -> Result<ReportOutcome, ReportError> {
+        self.runtime
+            .block_on(self.inner.report_absence(report, schedule))
+    }
+
+    /// See [`crate::User::get_news`]
+    #[cfg(feature = "unverified")]
+    pub fn get_news(&self) -> Result<Vec<NewsItem>, NewsError> {
+        self.runtime.block_on(self.inner.get_news())
+    }
+
+    /// See [`crate::User::get_news_for`]
+    #[cfg(feature = "unverified")]
+    pub fn get_news_for(&self, org: &Org) -> Result<Vec<NewsItem>, NewsError> {
+        self.runtime.block_on(self.inner.get_news_for(org))
+    }
+
+    /// See [`crate::User::get_schedule`]
+    pub fn get_schedule(&self) -> Result<Schedule, ScheduleError> {
+        self.runtime.block_on(self.inner.get_schedule())
+    }
+
+    /// See [`crate::User::get_schedule_for`]
+    pub fn get_schedule_for(&self, org: &Org) -> Result<Schedule, ScheduleError> {
+        self.runtime.block_on(self.inner.get_schedule_for(org))
+    }
+
+    /// See [`crate::User::get_all_schedules`]
+    pub fn get_all_schedules(&self) -> Result<Schedule, ScheduleError> {
+        self.runtime.block_on(self.inner.get_all_schedules())
+    }
+
+    /// Get the async user
+    pub fn into_async(self) -> crate::User {
+        self.inner
+    }
+}
+
+impl Deref for User {
+    type Target = crate::User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for User {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,453 @@
+//! Caching responses that rarely change
+//!
+//! The schedule endpoint returns the entire year every time and the school list contains every
+//! school using schoolsoft, neither changes more than a few times per term. Set a [`Cache`] with
+//! [`crate::ClientBuilder::cache`] to keep responses around for a while instead of downloading
+//! them again on every call.
+//!
+//! Responses are cached per [`Endpoint`] with a time to live for each. When a cached response has
+//! expired and schoolsoft included an `ETag` or `Last-Modified` header with it, the next request
+//! asks schoolsoft if it has changed instead of downloading it again.
+
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+
+/// The endpoints that can be cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Endpoint {
+    /// [`crate::Client::schools`]
+    Schools,
+
+    /// [`crate::User::get_lunch`]
+    Lunch,
+
+    /// [`crate::User::get_schedule`]
+    Schedule,
+
+    /// [`crate::User::get_children`]
+    #[cfg(feature = "unverified")]
+    Children,
+
+    /// [`crate::User::get_news`]
+    #[cfg(feature = "unverified")]
+    News,
+
+    /// [`crate::User::get_planning`]
+    #[cfg(feature = "unverified")]
+    Planning,
+
+    /// [`crate::User::get_grades`]
+    #[cfg(feature = "unverified")]
+    Grades,
+
+    /// [`crate::User::get_absences`]
+    #[cfg(feature = "unverified")]
+    Absences,
+}
+
+/// What a cached response is stored under
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub endpoint: Endpoint,
+
+    /// Id of the user the response belongs to, `None` for responses that are the same for all
+    pub user: Option<u32>,
+
+    pub url: String,
+}
+
+/// A cached response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub body: String,
+
+    /// When the response was received, or last confirmed to be unchanged
+    pub stored: NaiveDateTime,
+
+    /// Value of the `ETag` header, used to ask if the response has changed
+    pub etag: Option<String>,
+
+    /// Value of the `Last-Modified` header, used to ask if the response has changed
+    pub last_modified: Option<String>,
+}
+
+/// Somewhere to keep cached responses
+///
+/// Caching is best effort, so stores don't return errors. A store that can't read an entry should
+/// act as if it doesn't exist.
+pub trait CacheStore: Debug + Send + Sync {
+    /// Get the entry stored under `key`
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry>;
+
+    /// Store an entry, replacing any previous entry with the same key
+    fn put(&self, key: &CacheKey, entry: CacheEntry);
+
+    /// Remove every entry with a key that `matches`
+    fn remove_matching(&self, matches: &dyn Fn(&CacheKey) -> bool);
+}
+
+/// Keeps cached responses in memory
+#[derive(Debug, Default)]
+pub struct MemoryCacheStore {
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+/// Keeps cached responses as json files in a directory, one file per response
+///
+/// Responses can contain personal information, so on unix the files are created readable by the
+/// owner only.
+#[derive(Debug, Clone)]
+pub struct FileCacheStore {
+    directory: PathBuf,
+}
+
+/// Response cache shared by a [`crate::Client`] and every user it creates
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use schoolsoft::{ClientBuilder, cache::{Cache, Endpoint}};
+/// let client = ClientBuilder::new()
+///     .cache(Cache::memory().ttl(Endpoint::Lunch, Duration::from_secs(60 * 10)))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache {
+    store: Arc<dyn CacheStore>,
+    ttls: HashMap<Endpoint, Duration>,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Schools => write!(f, "schools"),
+            Endpoint::Lunch => write!(f, "lunch"),
+            Endpoint::Schedule => write!(f, "schedule"),
+            #[cfg(feature = "unverified")]
+            Endpoint::Children => write!(f, "children"),
+            #[cfg(feature = "unverified")]
+            Endpoint::News => write!(f, "news"),
+            #[cfg(feature = "unverified")]
+            Endpoint::Planning => write!(f, "planning"),
+            #[cfg(feature = "unverified")]
+            Endpoint::Grades => write!(f, "grades"),
+            #[cfg(feature = "unverified")]
+            Endpoint::Absences => write!(f, "absences"),
+        }
+    }
+}
+
+impl Cache {
+    /// Create a cache using `store` with the default time to live for every endpoint
+    ///
+    /// The defaults are:
+    /// - [`Endpoint::Schools`]: 1 day
+    /// - [`Endpoint::Lunch`]: 1 hour
+    /// - [`Endpoint::Schedule`]: 6 hours
+    ///
+    /// And with the `unverified` feature:
+    /// - `Endpoint::Children`: 1 day
+    /// - `Endpoint::News`: 15 minutes
+    /// - `Endpoint::Planning`: 1 hour
+    /// - `Endpoint::Grades`: 6 hours
+    /// - `Endpoint::Absences`: 15 minutes
+    pub fn new(store: impl CacheStore + 'static) -> Self {
+        #[cfg_attr(not(feature = "unverified"), allow(unused_mut))]
+        let mut ttls = HashMap::from([
+            (Endpoint::Schools, Duration::from_secs(60 * 60 * 24)),
+            (Endpoint::Lunch, Duration::from_secs(60 * 60)),
+            (Endpoint::Schedule, Duration::from_secs(60 * 60 * 6)),
+        ]);
+
+        #[cfg(feature = "unverified")]
+        ttls.extend([
+            (Endpoint::Children, Duration::from_secs(60 * 60 * 24)),
+            (Endpoint::News, Duration::from_secs(60 * 15)),
+            (Endpoint::Planning, Duration::from_secs(60 * 60)),
+            (Endpoint::Grades, Duration::from_secs(60 * 60 * 6)),
+            (Endpoint::Absences, Duration::from_secs(60 * 15)),
+        ]);
+
+        Self {
+            store: Arc::new(store),
+            ttls,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Create a cache that keeps responses in memory
+    pub fn memory() -> Self {
+        Self::new(MemoryCacheStore::new())
+    }
+
+    /// Create a cache that keeps responses in `directory`
+    pub fn file(directory: impl Into<PathBuf>) -> Self {
+        Self::new(FileCacheStore::new(directory))
+    }
+
+    /// Set how long responses from `endpoint` are used before asking schoolsoft again
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// Stop caching responses from `endpoint`
+    pub fn disable(mut self, endpoint: Endpoint) -> Self {
+        self.ttls.remove(&endpoint);
+        self
+    }
+
+    /// Remove every cached response from `endpoint`
+    pub fn invalidate(&self, endpoint: Endpoint) {
+        self.store.remove_matching(&|key| key.endpoint == endpoint);
+    }
+
+    /// Remove every cached response belonging to the user with the id `user`
+    pub fn invalidate_user(&self, user: u32) {
+        self.store.remove_matching(&|key| key.user == Some(user));
+    }
+
+    /// Remove every cached response
+    pub fn clear(&self) {
+        self.store.remove_matching(&|_| true);
+    }
+
+    /// Check if responses from `endpoint` are cached
+    pub(crate) fn is_enabled(&self, endpoint: Endpoint) -> bool {
+        self.ttls.contains_key(&endpoint)
+    }
+
+    /// Get a cached response
+    ///
+    /// # Returns
+    /// The entry together with `true` if it is still fresh, or `false` if it has expired and has
+    /// to be checked with schoolsoft first.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<(CacheEntry, bool)> {
+        let ttl = self.ttls.get(&key.endpoint)?;
+        let entry = self.store.get(key)?;
+
+        let age = self.clock.now() - entry.stored;
+        let fresh = age.to_std().map_or(true, |age| age < *ttl);
+
+        Some((entry, fresh))
+    }
+
+    /// Store a response, or mark an existing one as unchanged
+    pub(crate) fn put(&self, key: &CacheKey, mut entry: CacheEntry) {
+        entry.stored = self.clock.now();
+        self.store.put(key, entry);
+    }
+}
+
+impl MemoryCacheStore {
+    /// Create a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry) {
+        self.entries.lock().unwrap().insert(key.clone(), entry);
+    }
+
+    fn remove_matching(&self, matches: &dyn Fn(&CacheKey) -> bool) {
+        self.entries.lock().unwrap().retain(|key, _| !matches(key));
+    }
+}
+
+/// What is written to each file by [`FileCacheStore`]
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key: CacheKey,
+    entry: CacheEntry,
+}
+
+impl FileCacheStore {
+    /// Create a store that keeps responses in `directory`
+    ///
+    /// The directory is created when the first response is stored.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Get the directory responses are stored in
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Get the file a key is stored in
+    ///
+    /// Named after a FNV-1a hash of the key, which unlike the std hashers stays the same between
+    /// Rust releases so the files can still be found after upgrading.
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        let user = key.user.map(|user| user.to_string()).unwrap_or_default();
+        let hash = fnv1a(format!("{}\n{}\n{}", key.endpoint, user, key.url).as_bytes());
+
+        self.directory
+            .join(format!("{}-{:016x}.json", key.endpoint, hash))
+    }
+
+    fn read(path: &Path) -> Option<StoredEntry> {
+        let data = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn write(&self, path: &Path, stored: &StoredEntry) -> std::io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        let data = serde_json::to_vec(stored)?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        options.open(path)?.write_all(&data)
+    }
+}
+
+impl CacheStore for FileCacheStore {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        // Different keys could end up with the same file name, so check that it is the right one
+        Self::read(&self.path(key))
+            .filter(|stored| &stored.key == key)
+            .map(|stored| stored.entry)
+    }
+
+    fn put(&self, key: &CacheKey, entry: CacheEntry) {
+        let stored = StoredEntry {
+            key: key.clone(),
+            entry,
+        };
+
+        // Failing to write only means the response will be downloaded again next time
+        let _ = self.write(&self.path(key), &stored);
+    }
+
+    fn remove_matching(&self, matches: &dyn Fn(&CacheKey) -> bool) {
+        let Ok(files) = fs::read_dir(&self.directory) else {
+            return;
+        };
+
+        for file in files.flatten() {
+            let path = file.path();
+
+            if Self::read(&path).is_some_and(|stored| matches(&stored.key)) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// 64 bit FNV-1a hash of `bytes`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use pretty_assertions::assert_eq;
+
+    fn key(endpoint: Endpoint, user: Option<u32>) -> CacheKey {
+        CacheKey {
+            endpoint,
+            user,
+            url: "https://sms.schoolsoft.se/mock_school/api/lessons/student/1".to_string(),
+        }
+    }
+
+    fn entry(body: &str) -> CacheEntry {
+        CacheEntry {
+            body: body.to_string(),
+            stored: NaiveDateTime::default(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        }
+    }
+
+    fn check_store(store: impl CacheStore) {
+        let schedule = key(Endpoint::Schedule, Some(1));
+        let lunch = key(Endpoint::Lunch, Some(2));
+
+        assert_eq!(store.get(&schedule), None);
+
+        store.put(&schedule, entry("schedule"));
+        store.put(&lunch, entry("lunch"));
+        assert_eq!(store.get(&schedule), Some(entry("schedule")));
+
+        store.remove_matching(&|key| key.user == Some(1));
+        assert_eq!(store.get(&schedule), None);
+        assert_eq!(store.get(&lunch), Some(entry("lunch")));
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(MemoryCacheStore::new());
+    }
+
+    #[test]
+    fn file_names() {
+        let store = FileCacheStore::new("cache");
+
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(
+            store.path(&key(Endpoint::Schedule, Some(1))),
+            Path::new("cache").join("schedule-1c4c3a65ced293c2.json")
+        );
+    }
+
+    #[test]
+    fn file_store() {
+        let directory =
+            std::env::temp_dir().join(format!("schoolsoft-cache-test-{}", std::process::id()));
+
+        check_store(FileCacheStore::new(&directory));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn expires() {
+        let start = NaiveDateTime::default();
+        let clock = Arc::new(FixedClock::new(start));
+
+        let mut cache = Cache::memory().ttl(Endpoint::Lunch, Duration::from_secs(60));
+        cache.clock = clock.clone();
+
+        let key = key(Endpoint::Lunch, Some(1));
+        cache.put(&key, entry("lunch"));
+        assert_eq!(cache.get(&key), Some((entry("lunch"), true)));
+
+        clock.advance(chrono::Duration::minutes(2));
+        assert_eq!(cache.get(&key).map(|(_, fresh)| fresh), Some(false));
+
+        // Disabled endpoints are never read from the cache
+        let cache = cache.disable(Endpoint::Lunch);
+        assert_eq!(cache.get(&key), None);
+    }
+}
@@ -0,0 +1,399 @@
+//! Recording requests and responses and playing them back without a network
+//!
+//! Wrap a transport in a [`RecordingTransport`] to keep every request the library makes together
+//! with the response it got. Save the resulting [`Cassette`] to a file and a [`ReplayTransport`]
+//! can later answer the same requests from it, which makes it possible to reproduce a bug report or
+//! turn a real session into a regression test.
+//!
+//! Passwords, app keys, tokens and cookies, in headers, urls and bodies, are replaced with
+//! [`REDACTED`] before anything is recorded, so cassettes can be shared. The library never checks
+//! them, so replaying works the same.
+//! Token expiry dates are moved to [`REPLAY_EXPIRY`] so a cassette keeps replaying after the
+//! recorded token would have expired.
+//!
+//! # Examples
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use schoolsoft::{
+//! #     cassette::{Cassette, RecordingTransport, ReplayTransport},
+//! #     transport::ReqwestTransport,
+//! #     ClientBuilder,
+//! # };
+//! # async fn run() {
+//! let recorder = Arc::new(RecordingTransport::new(ReqwestTransport::default()));
+//!
+//! let mut client = ClientBuilder::new().transport(recorder.clone()).build();
+//! client.login("username", "password", "school").await.unwrap();
+//! client.user.unwrap().get_schedule().await.unwrap();
+//!
+//! recorder.cassette().save("schedule.json").unwrap();
+//!
+//! // Later, without a network
+//! let replay = ReplayTransport::new(Cassette::load("schedule.json").unwrap());
+//! let mut client = ClientBuilder::new().transport(replay).build();
+//! # }
+//! ```
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    http::{redact_form, redact_headers, redact_query, Request, Response},
+    transport::{BoxFuture, HttpTransport},
+    types::error::{CassetteError, RequestError},
+};
+
+pub use crate::secret::REDACTED;
+
+/// Json fields in response bodies that are redacted
+const SECRET_JSON_FIELDS: [&str; 2] = ["appKey", "token"];
+
+/// Expiry date recorded for every token, far enough ahead that replayed tokens never expire
+pub const REPLAY_EXPIRY: &str = "9999-12-31 23:59:59.0";
+
+/// Requests and responses in the order they were made
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// A request and the response it got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// The parts of a [`Request`] that are recorded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// A recorded [`Response`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Transport that records every request sent through it, see [`Cassette`]
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    cassette: Mutex<Cassette>,
+}
+
+/// Transport that answers requests from a [`Cassette`] without a network
+///
+/// A request is answered with the first recorded interaction with the same method, url and body
+/// that hasn't been used yet. Secrets are redacted before comparing, the same way as when
+/// recording. Requests without one fail with
+/// [`RequestError::TransportError`].
+#[derive(Debug)]
+pub struct ReplayTransport {
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+/// No recorded interaction matched a request
+#[derive(Debug, thiserror::Error)]
+#[error("No recorded response for {method} {url}")]
+pub struct NoInteraction {
+    pub method: String,
+    pub url: String,
+}
+
+impl Cassette {
+    /// Read a cassette from a json file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        let data = fs::read_to_string(path).map_err(CassetteError::IoError)?;
+        serde_json::from_str(&data).map_err(CassetteError::SerdeError)
+    }
+
+    /// Write the cassette to a json file, replacing it if it exists
+    ///
+    /// Responses can contain personal information, so on unix the file is created readable by the
+    /// owner only.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        let path = path.as_ref();
+        let data = serde_json::to_vec_pretty(self).map_err(CassetteError::SerdeError)?;
+
+        // Same as the session store, never leave a half written cassette behind
+        let mut temp = path.to_path_buf().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&temp).map_err(CassetteError::IoError)?;
+        file.write_all(&data).map_err(CassetteError::IoError)?;
+        file.sync_all().map_err(CassetteError::IoError)?;
+
+        fs::rename(&temp, path).map_err(CassetteError::IoError)
+    }
+}
+
+impl RecordedRequest {
+    /// Record a request with its secrets redacted
+    pub fn new(request: &Request) -> Self {
+        Self {
+            method: request.method.to_string(),
+            url: redact_query(&request.url),
+            headers: redact_headers(&request.headers),
+            body: request.body.as_ref().map(|body| redact_form(body.expose())),
+        }
+    }
+
+    /// Check if `request` is the same as this one
+    fn matches(&self, request: &RecordedRequest) -> bool {
+        self.method == request.method && self.url == request.url && self.body == request.body
+    }
+}
+
+impl RecordedResponse {
+    /// Record a response with its secrets redacted
+    pub fn new(response: &Response) -> Self {
+        Self {
+            status: response.status.as_u16(),
+            headers: redact_headers(&response.headers),
+            body: redact_json(&response.body),
+        }
+    }
+
+    /// Turn the recorded response back into a [`Response`]
+    ///
+    /// Invalid status codes are replayed as 500.
+    pub fn to_response(&self) -> Response {
+        Response {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+        }
+    }
+}
+
+impl<T: HttpTransport> RecordingTransport<T> {
+    /// Record every request sent through `inner`
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+
+    /// Get a copy of everything recorded so far
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+
+    async fn record(&self, request: &Request) -> Result<Response, RequestError> {
+        let response = self.inner.send(request).await?;
+
+        self.cassette
+            .lock()
+            .unwrap()
+            .interactions
+            .push(Interaction {
+                request: RecordedRequest::new(request),
+                response: RecordedResponse::new(&response),
+            });
+
+        Ok(response)
+    }
+}
+
+impl<T: HttpTransport> HttpTransport for RecordingTransport<T> {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+        Box::pin(self.record(request))
+    }
+}
+
+impl ReplayTransport {
+    /// Answer requests from `cassette`
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: Mutex::new(
+                cassette
+                    .interactions
+                    .into_iter()
+                    .map(|interaction| (interaction, false))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Check if every recorded interaction has been replayed
+    pub fn is_finished(&self) -> bool {
+        self.interactions
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, used)| *used)
+    }
+
+    fn replay(&self, request: &Request) -> Result<Response, RequestError> {
+        let recorded = RecordedRequest::new(request);
+        let mut interactions = self.interactions.lock().unwrap();
+
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(interaction, used)| !used && interaction.request.matches(&recorded))
+            .ok_or_else(|| {
+                RequestError::TransportError(Box::new(NoInteraction {
+                    method: recorded.method.clone(),
+                    url: recorded.url.clone(),
+                }))
+            })?;
+
+        *used = true;
+        Ok(interaction.response.to_response())
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+        let response = self.replay(request);
+        Box::pin(async move { response })
+    }
+}
+
+/// Redact secret fields anywhere in a json body, other bodies are kept as they are
+fn redact_json(body: &str) -> String {
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return body.to_string();
+    };
+
+    if !redact_value(&mut value) {
+        return body.to_string();
+    }
+
+    value.to_string()
+}
+
+/// Redact secret fields in `value`
+///
+/// Objects with a token also get their `expiryDate` replaced with [`REPLAY_EXPIRY`].
+///
+/// # Returns
+/// If anything was redacted
+fn redact_value(value: &mut serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(fields) => {
+            let mut redacted = false;
+
+            let has_token = matches!(fields.get("token"), Some(serde_json::Value::String(_)));
+            if let (true, Some(serde_json::Value::String(expiry))) =
+                (has_token, fields.get_mut("expiryDate"))
+            {
+                *expiry = REPLAY_EXPIRY.to_string();
+            }
+
+            for (name, value) in fields.iter_mut() {
+                if SECRET_JSON_FIELDS.contains(&name.as_str()) && value.is_string() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                    redacted = true;
+                } else {
+                    redacted |= redact_value(value);
+                }
+            }
+
+            redacted
+        }
+        serde_json::Value::Array(values) => {
+            let mut redacted = false;
+
+            for value in values.iter_mut() {
+                redacted |= redact_value(value);
+            }
+
+            redacted
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn redacts_response_headers() {
+        let response = RecordedResponse::new(&Response {
+            status: StatusCode::OK,
+            headers: vec![
+                ("Set-Cookie".to_string(), "JSESSIONID=secret".to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            body: String::new(),
+        });
+
+        assert_eq!(
+            response.headers,
+            vec![
+                ("Set-Cookie".to_string(), REDACTED.to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn redacts_json() {
+        assert_eq!(
+            redact_json(r#"{"name":"Mock User","appKey":"secret","orgs":[{"token":"secret"}]}"#),
+            r#"{"appKey":"<redacted>","name":"Mock User","orgs":[{"token":"<redacted>"}]}"#
+        );
+
+        assert_eq!(
+            redact_json(r#"{"expiryDate":"2024-02-12 17:22:23.714","token":"secret"}"#),
+            r#"{"expiryDate":"9999-12-31 23:59:59.0","token":"<redacted>"}"#
+        );
+
+        // Bodies without secrets keep their formatting
+        assert_eq!(redact_json("[ 1, 2 ]"), "[ 1, 2 ]");
+    }
+
+    #[test]
+    fn replays_in_order() {
+        let response = |body: &str| RecordedResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: body.to_string(),
+        };
+        let request = RecordedRequest::new(&Request::get("http://fake/token"));
+
+        let replay = ReplayTransport::new(Cassette {
+            interactions: vec![
+                Interaction {
+                    request: request.clone(),
+                    response: response("first"),
+                },
+                Interaction {
+                    request,
+                    response: response("second"),
+                },
+            ],
+        });
+
+        let request = Request::get("http://fake/token");
+        assert_eq!(replay.replay(&request).unwrap().body, "first");
+        assert_eq!(replay.replay(&request).unwrap().body, "second");
+        assert!(replay.is_finished());
+        assert!(replay.replay(&request).is_err());
+    }
+}
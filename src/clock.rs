@@ -0,0 +1,119 @@
+//! Where the library gets the current time from
+//!
+//! Everything that depends on the current time, like checking if a [`crate::types::Token`] has
+//! expired or figuring out which year a week in the [`crate::schedule::Schedule`] belongs to, asks
+//! a [`Clock`]. The default [`SystemClock`] uses the system time, replace it with a
+//! [`FixedClock`] (or your own implementation) using [`crate::ClientBuilder::clock`] to control
+//! time in tests and simulations.
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, Utc};
+
+/// Source of the current time
+///
+/// Schoolsoft doesn't include time zones in any of its responses, so all times are naive and
+/// assumed to be in the same local time as the clock. The exception is token expiry dates, which
+/// are in UTC and compared with [`Clock::now_utc`].
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current date and time
+    fn now(&self) -> NaiveDateTime;
+
+    /// Get the current date and time in UTC
+    ///
+    /// Defaults to [`Self::now`], which is right for clocks that don't have a time zone, like
+    /// [`FixedClock`].
+    fn now_utc(&self) -> NaiveDateTime {
+        self.now()
+    }
+
+    /// Get the current date
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+}
+
+/// Clock that uses the local time of the system, and the system time in UTC for
+/// [`Clock::now_utc`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+/// Clock that only moves when told to
+///
+/// # Examples
+/// ```
+/// # use schoolsoft::clock::{Clock, FixedClock};
+/// # use chrono::{Duration, NaiveDate};
+/// let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+/// let clock = FixedClock::new(start);
+///
+/// assert_eq!(clock.now(), start);
+///
+/// clock.advance(Duration::hours(1));
+/// assert_eq!(clock.now(), start + Duration::hours(1));
+/// ```
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<NaiveDateTime>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn now_utc(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+impl FixedClock {
+    /// Create a clock stopped at `now`
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Allows plain functions to be used as a clock, see [`crate::types::Token::new_with_now`]
+impl Clock for fn() -> NaiveDateTime {
+    fn now(&self) -> NaiveDateTime {
+        self()
+    }
+}
+
+/// Lets a clock be shared, like keeping a handle to a [`FixedClock`] to move it while the client
+/// uses it
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> NaiveDateTime {
+        (**self).now()
+    }
+
+    fn now_utc(&self) -> NaiveDateTime {
+        (**self).now_utc()
+    }
+
+    fn today(&self) -> NaiveDate {
+        (**self).today()
+    }
+}
@@ -0,0 +1,67 @@
+//! Credentials used for logging in again when the app key stops working
+//!
+//! An app key is normally valid for a long time, but schoolsoft can revoke it at any point. Without
+//! a [`CredentialProvider`] the only thing a [`crate::User`] can do then is to return
+//! [`crate::types::error::RequestError::Unauthorized`]. With one attached it will login again,
+//! replace the app key and retry.
+
+use std::fmt::Debug;
+
+use crate::secret::Secret;
+
+/// Username and password for a schoolsoft account
+///
+/// The password is never included in the [`Debug`] output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: Secret,
+}
+
+/// Something that can hand out [`Credentials`] when a user needs to login again
+///
+/// [`Credentials`] implements this trait itself, which covers most cases. Implement it for your own
+/// type if the credentials should be read from somewhere else, like an environment variable or a
+/// secret manager, every time they are needed.
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// Get the credentials to login with
+    fn credentials(&self) -> Credentials;
+}
+
+impl Credentials {
+    /// Create new credentials
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::credentials::Credentials;
+    /// let credentials = Credentials::new("username", "password");
+    ///
+    /// assert_eq!(credentials.username, "username");
+    /// ```
+    pub fn new(username: impl Into<String>, password: impl Into<Secret>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl CredentialProvider for Credentials {
+    fn credentials(&self) -> Credentials {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_password() {
+        let credentials = Credentials::new("mock_username", "mock_password");
+        let debug = format!("{:?}", credentials);
+
+        assert!(debug.contains("mock_username"));
+        assert!(!debug.contains("mock_password"));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    report::ReportOutcome,
+    schedule::{Lesson, Schedule},
+    types::{error::AbsenceParseError, Absence, AbsenceScope, Reporter},
+    utils,
+};
+use serde::Deserialize;
+
+use super::Deserializer;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAbsence {
+    id: u64,
+    date: String,
+
+    /// Id of the occasion, 0 when the whole day was missed
+    #[serde(default)]
+    lesson_id: u64,
+    length: u32,
+    #[serde(default)]
+    reason: Option<String>,
+    valid: bool,
+    cre_by_type: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawReportResponse {
+    success: bool,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    absence_ids: Vec<u64>,
+}
+
+impl Absence {
+    /// Find the lesson the absence is for in `schedule`
+    ///
+    /// # Returns
+    /// `None` if the absence is for the whole day, or if the lesson isn't in the schedule
+    pub fn lesson<'a>(&self, schedule: &'a Schedule) -> Option<&'a Lesson> {
+        let AbsenceScope::Lesson(occasion_id) = self.scope else {
+            return None;
+        };
+
+        schedule
+            .day(self.date)?
+            .lessons
+            .iter()
+            .find(|lesson| lesson.occasion_id == occasion_id)
+    }
+
+    /// Deserialize a list of absences from a JSON string
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::{Absence, AbsenceScope, Reporter};
+    /// let absences = Absence::deserialize_many(
+    ///   r#"[{
+    ///     "id": 9002,
+    ///     "date": "2024-04-04",
+    ///     "lessonId": 0,
+    ///     "length": 480,
+    ///     "valid": false,
+    ///     "creByType": 2
+    ///   }]"#,
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert_eq!(absences[0].scope, AbsenceScope::WholeDay);
+    /// assert_eq!(absences[0].reported_by, Reporter::Parent);
+    /// ```
+    pub fn deserialize_many(json: &str) -> Result<Vec<Absence>, AbsenceParseError> {
+        let raw: Vec<RawAbsence> =
+            serde_json::from_str(json).map_err(AbsenceParseError::SerdeError)?;
+
+        raw.into_iter()
+            .map(|raw| {
+                Ok(Absence {
+                    id: raw.id,
+                    date: utils::parse_date(&raw.date)
+                        .map_err(|err| AbsenceParseError::DateParseError(raw.date.clone(), err))?,
+                    scope: match raw.lesson_id {
+                        0 => AbsenceScope::WholeDay,
+                        id => AbsenceScope::Lesson(id),
+                    },
+                    minutes: raw.length,
+                    reason: raw.reason.filter(|reason| !reason.is_empty()),
+                    valid: raw.valid,
+                    reported_by: Reporter::from(raw.cre_by_type),
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<i32> for Reporter {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Reporter::Student,
+            2 => Reporter::Parent,
+            3 => Reporter::Staff,
+            other => Reporter::Other(other),
+        }
+    }
+}
+
+impl Deserializer for ReportOutcome {
+    type Error = serde_json::Error;
+
+    fn deserialize(data: &str) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let raw: RawReportResponse = serde_json::from_str(data)?;
+
+        Ok(match raw.success {
+            true => ReportOutcome::Accepted(raw.absence_ids),
+            false => ReportOutcome::Rejected(raw.message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    const DATA: &str = r#"[
+        {
+            "id": 9001,
+            "date": "2024-04-03",
+            "lessonId": 1234,
+            "length": 60,
+            "reason": "Tandläkare",
+            "valid": true,
+            "creByType": 2
+        },
+        {
+            "id": 9002,
+            "date": "2024-04-04",
+            "lessonId": 0,
+            "length": 420,
+            "reason": "",
+            "valid": false,
+            "creByType": -1
+        }
+    ]"#;
+
+    #[test]
+    fn test_deserialize() {
+        let absences = Absence::deserialize_many(DATA).unwrap();
+
+        assert_eq!(
+            absences,
+            vec![
+                Absence {
+                    id: 9001,
+                    date: NaiveDate::from_ymd_opt(2024, 4, 3).unwrap(),
+                    scope: AbsenceScope::Lesson(1234),
+                    minutes: 60,
+                    reason: Some("Tandläkare".to_string()),
+                    valid: true,
+                    reported_by: Reporter::Parent,
+                },
+                Absence {
+                    id: 9002,
+                    date: NaiveDate::from_ymd_opt(2024, 4, 4).unwrap(),
+                    scope: AbsenceScope::WholeDay,
+                    minutes: 420,
+                    reason: None,
+                    valid: false,
+                    reported_by: Reporter::Other(-1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lesson() {
+        let absences = Absence::deserialize_many(DATA).unwrap();
+
+        let mut schedule = Schedule::from(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        schedule.weeks[13].wednesday.lessons.push(Lesson {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            name: "Math".to_string(),
+            room: "A1".to_string(),
+            org_id: 1,
+            occasion_id: 1234,
+        });
+
+        assert_eq!(absences[0].lesson(&schedule).unwrap().name, "Math");
+        assert_eq!(absences[1].lesson(&schedule), None);
+    }
+}
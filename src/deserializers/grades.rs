@@ -0,0 +1,156 @@
+use crate::{
+    types::{error::GradesParseError, Grade},
+    utils,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawGrade {
+    course_code: String,
+    course_name: String,
+
+    /// 0, null or missing for courses without points
+    #[serde(default)]
+    points: Option<u32>,
+    grade: String,
+
+    /// Empty, null or missing when the date isn't known
+    #[serde(default)]
+    grade_date: Option<String>,
+    #[serde(default)]
+    teacher_name: Option<String>,
+}
+
+impl Grade {
+    /// Deserialize a list of grades from a JSON string
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::Grade;
+    /// let grades = Grade::deserialize_many(
+    ///   r#"[{
+    ///     "courseCode": "GYARAR",
+    ///     "courseName": "Gymnasiearbete",
+    ///     "points": 0,
+    ///     "grade": "E",
+    ///     "gradeDate": ""
+    ///   }]"#,
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert_eq!(grades[0].points, None);
+    /// assert_eq!(grades[0].date, None);
+    /// ```
+    pub fn deserialize_many(json: &str) -> Result<Vec<Grade>, GradesParseError> {
+        let raw: Vec<RawGrade> =
+            serde_json::from_str(json).map_err(GradesParseError::SerdeError)?;
+
+        raw.into_iter()
+            .map(|raw| {
+                let date = match raw.grade_date.as_deref() {
+                    None | Some("") => None,
+                    // Dates sometimes include a time, which is always midnight
+                    Some(date) => Some(
+                        utils::parse_date(date.get(..10).unwrap_or(date)).map_err(|err| {
+                            GradesParseError::DateParseError(date.to_string(), err)
+                        })?,
+                    ),
+                };
+
+                Ok(Grade {
+                    course_code: raw.course_code,
+                    course_name: raw.course_name,
+                    points: raw.points.filter(|points| *points != 0),
+                    grade: raw.grade,
+                    date,
+                    teacher: raw.teacher_name.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_deserialize() {
+        let data = r#"[
+            {
+                "courseCode": "MATMAT01c",
+                "courseName": "Matematik 1c",
+                "points": 100,
+                "grade": "A",
+                "gradeDate": "2024-01-19 00:00:00.0",
+                "teacherName": "Mock Teacher"
+            },
+            {
+                "courseCode": "GYARAR",
+                "courseName": "Gymnasiearbete",
+                "points": 0,
+                "grade": "E",
+                "gradeDate": ""
+            }
+        ]"#;
+
+        let grades = Grade::deserialize_many(data).unwrap();
+
+        assert_eq!(
+            grades,
+            vec![
+                Grade {
+                    course_code: "MATMAT01c".to_string(),
+                    course_name: "Matematik 1c".to_string(),
+                    points: Some(100),
+                    grade: "A".to_string(),
+                    date: NaiveDate::from_ymd_opt(2024, 1, 19),
+                    teacher: "Mock Teacher".to_string(),
+                },
+                Grade {
+                    course_code: "GYARAR".to_string(),
+                    course_name: "Gymnasiearbete".to_string(),
+                    points: None,
+                    grade: "E".to_string(),
+                    date: None,
+                    teacher: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nulls() {
+        let data = r#"[{
+            "courseCode": "GYARAR",
+            "courseName": "Gymnasiearbete",
+            "points": null,
+            "grade": "E",
+            "gradeDate": null,
+            "teacherName": null
+        }]"#;
+
+        let grades = Grade::deserialize_many(data).unwrap();
+
+        assert_eq!(grades[0].points, None);
+        assert_eq!(grades[0].date, None);
+        assert_eq!(grades[0].teacher, "");
+    }
+
+    #[test]
+    fn bad_date() {
+        let data = r#"[{
+            "courseCode": "MATMAT01c",
+            "courseName": "Matematik 1c",
+            "grade": "A",
+            "gradeDate": "19/01/2024"
+        }]"#;
+
+        match Grade::deserialize_many(data) {
+            Err(GradesParseError::DateParseError(date, _)) if date == "19/01/2024" => (),
+            other => panic!("Expected a date error, got {:?}", other),
+        }
+    }
+}
@@ -44,7 +44,7 @@ impl Deserializer for LunchMenu {
             serde_json::from_str(data).map_err(LunchMenuParseError::SerdeError)?;
         let raw = raw
             .first()
-            .ok_or_else(|| LunchMenuParseError::NoLunchMenu)?;
+            .ok_or(LunchMenuParseError::NoLunchMenu)?;
 
         let dates = raw
             .dates
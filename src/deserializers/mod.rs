@@ -6,7 +6,15 @@
 //! But if you for example want to make a custom request to the SchoolSoft API,
 //! then you have the option.
 
+#[cfg(feature = "unverified")]
+pub mod absence;
+#[cfg(feature = "unverified")]
+pub mod grades;
 pub mod lunch;
+#[cfg(feature = "unverified")]
+pub mod news;
+#[cfg(feature = "unverified")]
+pub mod planning;
 pub mod school;
 pub mod user;
 
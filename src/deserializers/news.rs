@@ -0,0 +1,177 @@
+use crate::{
+    types::{error::NewsParseError, Attachment, NewsItem},
+    utils,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawNewsItem {
+    id: u64,
+    title: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    publisher: String,
+    from_date: String,
+    #[serde(default)]
+    to_date: Option<String>,
+    #[serde(default)]
+    read: bool,
+    #[serde(default)]
+    attachments: Vec<RawAttachment>,
+}
+
+/// Attachment as sent with both news and planning
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RawAttachment {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+impl From<RawAttachment> for Attachment {
+    fn from(raw: RawAttachment) -> Self {
+        Attachment {
+            id: raw.id,
+            name: raw.name,
+            size: raw.size,
+            content_type: raw.content_type,
+        }
+    }
+}
+
+/// Parse a date that might be missing the time, like `2024-02-16` or `2024-02-16 15:03:15.0`
+fn parse_date(raw: &str) -> Result<chrono::NaiveDateTime, NewsParseError> {
+    utils::parse_datetime(raw)
+        .or_else(|_| utils::parse_date(raw).map(|date| date.and_time(chrono::NaiveTime::MIN)))
+        .map_err(|err| NewsParseError::DateParseError(raw.to_string(), err))
+}
+
+impl NewsItem {
+    /// Deserialize a list of news items from a JSON string
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::NewsItem;
+    /// let news = NewsItem::deserialize_many(
+    ///   r#"[{
+    ///     "id": 4712,
+    ///     "title": "Utvecklingssamtal",
+    ///     "fromDate": "2024-02-12",
+    ///     "toDate": ""
+    ///   }]"#,
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert_eq!(news[0].published.to_string(), "2024-02-12 00:00:00");
+    /// assert_eq!(news[0].expires, None);
+    /// ```
+    pub fn deserialize_many(json: &str) -> Result<Vec<NewsItem>, NewsParseError> {
+        let raw: Vec<RawNewsItem> =
+            serde_json::from_str(json).map_err(NewsParseError::SerdeError)?;
+
+        raw.into_iter()
+            .map(|raw| {
+                Ok(NewsItem {
+                    id: raw.id,
+                    published: parse_date(&raw.from_date)?,
+                    expires: raw
+                        .to_date
+                        .as_deref()
+                        .filter(|date| !date.is_empty())
+                        .map(parse_date)
+                        .transpose()?,
+                    title: raw.title,
+                    body: raw.text,
+                    author: raw.publisher,
+                    read: raw.read,
+                    attachments: raw.attachments.into_iter().map(Attachment::from).collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_deserialize() {
+        let data = r#"[
+            {
+                "id": 4711,
+                "title": "Sportlov",
+                "text": "<p>Skolan är stängd vecka 9.</p>",
+                "publisher": "Mock Teacher",
+                "fromDate": "2024-02-16 15:03:15.0",
+                "toDate": "2024-03-04",
+                "read": true,
+                "attachments": [
+                    {
+                        "id": 12,
+                        "name": "schema.pdf",
+                        "size": 48213,
+                        "contentType": "application/pdf"
+                    }
+                ]
+            },
+            {
+                "id": 4712,
+                "title": "Utvecklingssamtal",
+                "fromDate": "2024-02-12",
+                "toDate": ""
+            }
+        ]"#;
+
+        let news = NewsItem::deserialize_many(data).unwrap();
+        assert_eq!(news.len(), 2);
+
+        assert_eq!(news[0].id, 4711);
+        assert_eq!(news[0].body, "<p>Skolan är stängd vecka 9.</p>");
+        assert_eq!(news[0].author, "Mock Teacher");
+        assert_eq!(
+            news[0].published,
+            NaiveDate::from_ymd_opt(2024, 2, 16)
+                .unwrap()
+                .and_hms_opt(15, 3, 15)
+                .unwrap()
+        );
+        assert_eq!(
+            news[0].expires,
+            NaiveDate::from_ymd_opt(2024, 3, 4)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+        assert!(news[0].read);
+        assert_eq!(
+            news[0].attachments,
+            vec![Attachment {
+                id: 12,
+                name: "schema.pdf".to_string(),
+                size: Some(48213),
+                content_type: Some("application/pdf".to_string()),
+            }]
+        );
+
+        assert_eq!(news[1].body, "");
+        assert_eq!(news[1].expires, None);
+        assert!(!news[1].read);
+        assert!(news[1].attachments.is_empty());
+    }
+
+    #[test]
+    fn bad_date() {
+        let data = r#"[{ "id": 1, "title": "", "fromDate": "tomorrow" }]"#;
+
+        match NewsItem::deserialize_many(data) {
+            Err(NewsParseError::DateParseError(date, _)) if date == "tomorrow" => (),
+            other => panic!("Expected a date error, got {:?}", other),
+        }
+    }
+}
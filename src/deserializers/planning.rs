@@ -0,0 +1,189 @@
+use crate::{
+    schedule::{Lesson, Schedule},
+    types::{error::PlanningParseError, Attachment, PlanningItem, PlanningKind},
+    utils,
+};
+use serde::Deserialize;
+
+use super::news::RawAttachment;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPlanningItem {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: RawPlanningKind,
+    title: String,
+    #[serde(default)]
+    description: String,
+    subject_name: String,
+    due_date: String,
+
+    /// Id of the occasion, 0 when the item doesn't belong to a lesson
+    #[serde(default)]
+    lesson_id: u64,
+    #[serde(default)]
+    attachments: Vec<RawAttachment>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RawPlanningKind {
+    Assignment,
+    Test,
+    Deadline,
+    #[serde(other)]
+    Other,
+}
+
+impl From<RawPlanningKind> for PlanningKind {
+    fn from(raw: RawPlanningKind) -> Self {
+        match raw {
+            RawPlanningKind::Assignment => PlanningKind::Assignment,
+            RawPlanningKind::Test => PlanningKind::Test,
+            RawPlanningKind::Deadline => PlanningKind::Deadline,
+            RawPlanningKind::Other => PlanningKind::Other,
+        }
+    }
+}
+
+impl PlanningItem {
+    /// Find the lesson the item belongs to in `schedule`
+    ///
+    /// # Returns
+    /// `None` if the item doesn't belong to a lesson, or if the lesson isn't in the schedule on the
+    /// day the item is due
+    pub fn lesson<'a>(&self, schedule: &'a Schedule) -> Option<&'a Lesson> {
+        let occasion_id = self.occasion_id?;
+
+        schedule
+            .day(self.due.date())?
+            .lessons
+            .iter()
+            .find(|lesson| lesson.occasion_id == occasion_id)
+    }
+
+    /// Deserialize a list of planning items from a JSON string
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::{PlanningItem, PlanningKind};
+    /// let planning = PlanningItem::deserialize_many(
+    ///   r#"[{
+    ///     "id": 31,
+    ///     "type": "test",
+    ///     "title": "Prov kapitel 3",
+    ///     "subjectName": "Matematik",
+    ///     "dueDate": "2024-03-14 08:15:00.0",
+    ///     "lessonId": 0
+    ///   }]"#,
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert_eq!(planning[0].kind, PlanningKind::Test);
+    /// assert_eq!(planning[0].occasion_id, None);
+    /// ```
+    pub fn deserialize_many(json: &str) -> Result<Vec<PlanningItem>, PlanningParseError> {
+        let raw: Vec<RawPlanningItem> =
+            serde_json::from_str(json).map_err(PlanningParseError::SerdeError)?;
+
+        raw.into_iter()
+            .map(|raw| {
+                Ok(PlanningItem {
+                    id: raw.id,
+                    kind: PlanningKind::from(raw.kind),
+                    due: utils::parse_datetime(&raw.due_date).map_err(|err| {
+                        PlanningParseError::DateParseError(raw.due_date.clone(), err)
+                    })?,
+                    title: raw.title,
+                    description: raw.description,
+                    course: raw.subject_name,
+                    occasion_id: Some(raw.lesson_id).filter(|id| *id != 0),
+                    attachments: raw.attachments.into_iter().map(Attachment::from).collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+    use pretty_assertions::assert_eq;
+
+    const DATA: &str = r#"[
+        {
+            "id": 501,
+            "type": "test",
+            "title": "Prov kapitel 3",
+            "description": "Derivata och integraler",
+            "subjectName": "Matematik 1c",
+            "dueDate": "2024-04-03 08:00:00.0",
+            "lessonId": 1234,
+            "attachments": [
+                {
+                    "id": 7,
+                    "name": "repetition.pdf",
+                    "size": 20480
+                }
+            ]
+        },
+        {
+            "id": 502,
+            "type": "presentation",
+            "title": "Muntlig redovisning",
+            "subjectName": "Svenska 1",
+            "dueDate": "2024-04-05 23:59:00.0"
+        }
+    ]"#;
+
+    #[test]
+    fn test_deserialize() {
+        let planning = PlanningItem::deserialize_many(DATA).unwrap();
+
+        assert_eq!(
+            planning[0],
+            PlanningItem {
+                id: 501,
+                kind: PlanningKind::Test,
+                title: "Prov kapitel 3".to_string(),
+                description: "Derivata och integraler".to_string(),
+                course: "Matematik 1c".to_string(),
+                due: NaiveDate::from_ymd_opt(2024, 4, 3)
+                    .unwrap()
+                    .and_hms_opt(8, 0, 0)
+                    .unwrap(),
+                occasion_id: Some(1234),
+                attachments: vec![Attachment {
+                    id: 7,
+                    name: "repetition.pdf".to_string(),
+                    size: Some(20480),
+                    content_type: None,
+                }],
+            }
+        );
+
+        assert_eq!(planning[1].kind, PlanningKind::Other);
+        assert_eq!(planning[1].description, "");
+        assert_eq!(planning[1].occasion_id, None);
+        assert!(planning[1].attachments.is_empty());
+    }
+
+    #[test]
+    fn lesson() {
+        let planning = PlanningItem::deserialize_many(DATA).unwrap();
+
+        let mut schedule = Schedule::from(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        schedule.weeks[13].wednesday.lessons.push(Lesson {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            name: "Math".to_string(),
+            room: "A1".to_string(),
+            org_id: 1,
+            occasion_id: 1234,
+        });
+
+        assert_eq!(planning[0].lesson(&schedule).unwrap().name, "Math");
+        assert_eq!(planning[1].lesson(&schedule), None);
+    }
+}
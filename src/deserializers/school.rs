@@ -1,7 +1,7 @@
 
 use crate::types::error;
 use serde::{de::Error, Deserialize};
-use crate::types::{SchoolListing, LoginMethods};
+use crate::types::{LoginMethod, LoginMethods, SchoolListing, UserType};
 
 use super::Deserializer;
 
@@ -30,19 +30,67 @@ impl Deserializer for SchoolListing {
     }
 }
 
+impl From<u8> for LoginMethod {
+    fn from(value: u8) -> Self {
+        match value {
+            4 => LoginMethod::App,
+            other => LoginMethod::Other(other),
+        }
+    }
+}
+
+impl From<LoginMethod> for u8 {
+    fn from(value: LoginMethod) -> Self {
+        match value {
+            LoginMethod::App => 4,
+            LoginMethod::Other(other) => other,
+        }
+    }
+}
+
+impl LoginMethods {
+    /// Get the login methods available for a type of user
+    pub fn for_user(&self, user_type: UserType) -> &[LoginMethod] {
+        match user_type {
+            UserType::Student => &self.student,
+            UserType::Parent => &self.parent,
+            UserType::Teacher => &self.teacher,
+        }
+    }
+
+    /// Check if a type of user can login through the app api
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::types::{LoginMethod, LoginMethods, UserType};
+    /// let methods = LoginMethods {
+    ///     student: vec![LoginMethod::Other(0), LoginMethod::App],
+    ///     teacher: vec![LoginMethod::Other(0)],
+    ///     parent: vec![],
+    /// };
+    ///
+    /// assert!(methods.supports_app(UserType::Student));
+    /// assert!(!methods.supports_app(UserType::Teacher));
+    /// assert!(!methods.supports_app(UserType::Parent));
+    /// ```
+    pub fn supports_app(&self, user_type: UserType) -> bool {
+        self.for_user(user_type).contains(&LoginMethod::App)
+    }
+}
+
 impl SchoolListing {
-    fn parse_methods(raw: &str) -> Result<Vec<u8>, error::SchoolListingError> {
+    fn parse_methods(raw: &str) -> Result<Vec<LoginMethod>, error::SchoolListingError> {
         let mut methods = Vec::new();
         for method in raw.split(',') {
             if method.is_empty() {
                 continue;
             }
-            methods.push(
+            methods.push(LoginMethod::from(
                 method
-                    .parse()
+                    .parse::<u8>()
                     .map_err(serde_json::Error::custom)
                     .map_err(error::SchoolListingError::ParseError)?,
-            );
+            ));
         }
 
         Ok(methods)
@@ -104,9 +152,12 @@ mod tests {
         assert_eq!(school_listing.name, "Mock School");
         assert_eq!(school_listing.url, "https://sms.schoolsoft.se/mock/");
         assert_eq!(school_listing.url_name, "mock");
-        assert_eq!(school_listing.login_methods.student, vec![0, 1, 4]);
-        assert_eq!(school_listing.login_methods.teacher, vec![0]);
-        assert_eq!(school_listing.login_methods.parent, vec![4]);
+        assert_eq!(
+            school_listing.login_methods.student,
+            vec![LoginMethod::Other(0), LoginMethod::Other(1), LoginMethod::App]
+        );
+        assert_eq!(school_listing.login_methods.teacher, vec![LoginMethod::Other(0)]);
+        assert_eq!(school_listing.login_methods.parent, vec![LoginMethod::App]);
     }
 
     #[test]
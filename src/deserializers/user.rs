@@ -1,12 +1,21 @@
+use std::sync::Arc;
+
 use super::Deserializer;
 use crate::{
+    clock::SystemClock,
+    device::DeviceProfile,
+    http::Http,
+    secret::Secret,
     types::{Org, User, UserType},
-    user::Token,
+    user::{Auth, Token},
 };
 use reqwest::Url;
 use serde::de::Error;
 use serde::Deserialize;
 
+#[cfg(feature = "unverified")]
+use crate::types::Child;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -27,21 +36,49 @@ struct RawUser {
     pub picture_url: String,
     pub name: String,
     pub is_of_age: bool,
-    pub app_key: String,
+    pub app_key: Secret,
     pub orgs: Vec<RawOrg>,
     #[serde(rename = "type")]
     pub user_type: u8,
     pub user_id: u32,
 }
 
+impl RawUser {
+    /// Get the school url from the token login url of the first org
+    ///
+    /// The school url is only included in the token login urls, so it is `None` for users that
+    /// aren't part of any org.
+    fn school_url(&self) -> Result<Option<Url>, serde_json::Error> {
+        let Some(first) = self.orgs.first() else {
+            return Ok(None);
+        };
+
+        // There must be a better way to only get the first part of the path without any query args or fragments
+        let mut school_url = Url::parse(&first.token_login).map_err(serde_json::Error::custom)?;
+        let school = school_url
+            .path_segments()
+            .and_then(|mut segments| segments.next())
+            .filter(|school| !school.is_empty())
+            .ok_or(serde_json::Error::custom("Token login url doesn't contain a school"))?
+            .to_string();
+        school_url.set_path(&school);
+        school_url.set_query(None);
+        school_url.set_fragment(None);
+
+        Ok(Some(school_url))
+    }
+}
+
 impl Deserializer for User {
     type Error = serde_json::Error;
 
     /// Deserialize a user from a JSON String
     ///
+    /// The school url is taken from the token login url of the first org, so users that aren't
+    /// part of any org can't be deserialized this way, see [`User::deserialize_with_url`].
+    ///
     /// # Arguments
     /// * `json` - A JSON string containing the user data
-    /// * `school_url` - The url of the school the user is logging in to
     ///
     /// # Example
     /// ```
@@ -71,32 +108,121 @@ impl Deserializer for User {
     fn deserialize(json: &str) -> Result<User, serde_json::Error> {
         let raw: RawUser = serde_json::from_str(json)?;
 
+        let school_url = raw
+            .school_url()?
+            .ok_or(serde_json::Error::custom("User is not part of any organization"))?;
+
+        User::from_raw(raw, school_url)
+    }
+}
+
+impl User {
+    /// Deserialize a user from a login response sent by the school at `school_url`
+    ///
+    /// The school url is taken from the token login url of the first org, like
+    /// [`Deserializer::deserialize`] does. `school_url` is only used for users that aren't part of
+    /// any org, which can't be deserialized without it. Requests that need an org fail with
+    /// [`crate::types::error::RequestError::NoOrganization`] for those users.
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::User;
+    /// # use reqwest::Url;
+    /// let user = User::deserialize_with_url(
+    ///     r#"{
+    ///         "pictureUrl": "pictureFile.jsp?studentId=1337",
+    ///         "name": "Mock User",
+    ///         "isOfAge": false,
+    ///         "appKey": "123notreal",
+    ///         "orgs": [],
+    ///         "type": 1,
+    ///         "userId": 1337
+    ///     }"#,
+    ///     Url::parse("https://sms.schoolsoft.se/mock_school").unwrap(),
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert!(user.org().is_none());
+    /// assert_eq!(user.school_url.as_str(), "https://sms.schoolsoft.se/mock_school");
+    /// ```
+    pub fn deserialize_with_url(json: &str, school_url: Url) -> Result<User, serde_json::Error> {
+        let raw: RawUser = serde_json::from_str(json)?;
+        let school_url = raw.school_url()?.unwrap_or(school_url);
+
+        User::from_raw(raw, school_url)
+    }
+
+    fn from_raw(raw: RawUser, school_url: Url) -> Result<User, serde_json::Error> {
         // This is technically unnecessary but allows for future functionality to be added to Org
         let orgs: Vec<Org> = raw.orgs.into_iter().map(Org::from).collect();
 
-        // There must be a better way to only get the first part of the path without any query args or fragments
-        let mut school_url = Url::parse(&orgs[0].token_login).unwrap();
-        let segments = school_url.clone();
-        school_url.set_path(segments.path_segments().unwrap().next().unwrap());
-        school_url.set_query(None);
-        school_url.set_fragment(None);
-
         Ok(User {
             school_url,
-            client: reqwest::Client::new(),
+            http: Http::default(),
             name: raw.name,
             pictute_url: raw.picture_url,
             is_of_age: raw.is_of_age,
-            app_key: raw.app_key,
-            token: None,
+            auth: Arc::new(Auth::new(raw.app_key)),
             user_type: UserType::from_u8(raw.user_type)
                 .ok_or(serde_json::Error::custom("Invalid user type"))?,
             id: raw.user_id,
             orgs,
+            credentials: None,
+            clock: Arc::new(SystemClock),
+            device: DeviceProfile::default(),
+            #[cfg(feature = "unverified")]
+            child: None,
+            org_id: None,
         })
     }
 }
 
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawChild {
+    pub user_id: u32,
+    pub name: String,
+    pub class: String,
+    pub org_id: u32,
+}
+
+#[cfg(feature = "unverified")]
+impl Child {
+    /// Deserialize a list of children from a JSON string
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::Child;
+    /// let children = Child::deserialize_many(
+    ///   r#"[{
+    ///     "userId": 1337,
+    ///     "name": "Mock Child",
+    ///     "class": "F35b",
+    ///     "orgId": 1
+    ///   }]"#,
+    /// ).expect("Failed to deserialize JSON");
+    ///
+    /// assert_eq!(children[0].id, 1337);
+    /// assert_eq!(children[0].name, "Mock Child");
+    /// ```
+    pub fn deserialize_many(json: &str) -> Result<Vec<Child>, serde_json::Error> {
+        let raw: Vec<RawChild> = serde_json::from_str(json)?;
+        Ok(raw.into_iter().map(Child::from).collect())
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl From<RawChild> for Child {
+    fn from(value: RawChild) -> Self {
+        Child {
+            id: value.user_id,
+            name: value.name,
+            class: value.class,
+            org_id: value.org_id,
+        }
+    }
+}
+
 impl Token {
     /// Deserialize a token from a JSON string
     ///
@@ -110,7 +236,7 @@ impl Token {
     ///    }"#,
     /// ).expect("Failed to deserialize JSON");
     ///
-    /// assert_eq!(token.token, "notrealtoken123_1337_1".to_string());
+    /// assert_eq!(token.token.expose(), "notrealtoken123_1337_1");
     /// assert_eq!(token.expires, chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap());
     /// ```
     pub fn deserialize(json: &str) -> Result<Token, serde_json::Error> {
@@ -118,20 +244,16 @@ impl Token {
         struct RawToken {
             #[serde(rename = "expiryDate")]
             expiry_date: String,
-            token: String,
+            token: Secret,
         }
 
         let raw_token: RawToken = serde_json::from_str(json)?;
 
-        Ok(Token {
-            now: || chrono::Utc::now().naive_utc(),
-            token: raw_token.token,
-            expires: chrono::NaiveDateTime::parse_from_str(
-                &raw_token.expiry_date,
-                "%Y-%m-%d %H:%M:%S%.f",
-            )
-            .map_err(serde_json::Error::custom)?,
-        })
+        Ok(Token::new(
+            raw_token.token,
+            chrono::NaiveDateTime::parse_from_str(&raw_token.expiry_date, "%Y-%m-%d %H:%M:%S%.f")
+                .map_err(serde_json::Error::custom)?,
+        ))
     }
 }
 
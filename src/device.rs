@@ -0,0 +1,70 @@
+//! How the library presents itself to schoolsoft
+//!
+//! The app sends a few headers describing the device it runs on. Schoolsoft doesn't seem to
+//! require any of them to be meaningful, but it presumably uses them for logging and analytics.
+//! Set a [`DeviceProfile`] with [`crate::ClientBuilder::device`] to control what is sent.
+
+use crate::http::Request;
+
+/// Identity of the device making requests
+///
+/// Sent with every request to the app api as the `deviceid`, `appos`, `appversion` and
+/// `User-Agent` headers.
+///
+/// # Examples
+/// ```
+/// # use schoolsoft::{ClientBuilder, device::DeviceProfile};
+/// let client = ClientBuilder::new()
+///     .device(DeviceProfile {
+///         device_id: "1234567890".to_string(),
+///         ..Default::default()
+///     })
+///     .build();
+///
+/// assert_eq!(client.device().os, "android");
+/// assert_eq!(client.device_id(), "1234567890");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceProfile {
+    /// Unique id of the device
+    ///
+    /// Default: "", which works without any known issues
+    pub device_id: String,
+
+    /// Operating system the app is running on
+    ///
+    /// Default: "android"
+    pub os: String,
+
+    /// Version of the schoolsoft app
+    ///
+    /// Default: "2.3.14"
+    pub app_version: String,
+
+    /// User agent of the http client
+    ///
+    /// Default: "schoolsoft/\[version of this crate\]"
+    pub user_agent: String,
+}
+
+impl Default for DeviceProfile {
+    fn default() -> Self {
+        Self {
+            device_id: String::new(),
+            os: "android".to_string(),
+            app_version: "2.3.14".to_string(),
+            user_agent: concat!("schoolsoft/", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+}
+
+impl DeviceProfile {
+    /// Add the device headers to a request
+    pub(crate) fn apply(&self, request: Request) -> Request {
+        request
+            .header("deviceid", &self.device_id)
+            .header("appos", &self.os)
+            .header("appversion", &self.app_version)
+            .header("user-agent", &self.user_agent)
+    }
+}
@@ -0,0 +1,552 @@
+//! Requests and responses as seen by the library
+//!
+//! Every request to the app api goes through the [`crate::Client`], which owns the
+//! [`HttpTransport`] and decides what happens around sending it, like retrying with a
+//! [`RetryPolicy`], waiting for the [`RateLimiter`], running [`Middleware`] or answering from the
+//! [`Cache`]. Requests and responses are kept as plain data in [`Request`] and [`Response`] so
+//! they can be cloned, sent again, inspected and changed.
+
+use std::{fmt::Debug, sync::Arc};
+
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use zeroize::Zeroize;
+
+use crate::{
+    cache::{Cache, CacheEntry, CacheKey, Endpoint},
+    clock::{Clock, SystemClock},
+    instrument,
+    middleware::Middleware,
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    secret::{Secret, REDACTED},
+    transport::{HttpConfig, HttpTransport, ReqwestTransport},
+    types::error::RequestError,
+    utils::check_codes,
+};
+
+/// Headers that carry credentials or sessions, compared ignoring case
+pub(crate) const SECRET_HEADERS: [&str; 5] =
+    ["token", "appkey", "cookie", "set-cookie", "authorization"];
+
+/// Form fields that carry credentials
+pub(crate) const SECRET_FORM_FIELDS: [&str; 1] = ["verification"];
+
+/// Query parameters that carry credentials, like the token in [`crate::types::Org::token_login`]
+pub(crate) const SECRET_QUERY_PARAMETERS: [&str; 3] = ["token", "appkey", "verification"];
+
+/// A request that hasn't been sent yet
+///
+/// The values of headers and query parameters that carry credentials, like `token` and `appkey`,
+/// and the password in a login form are replaced with [`REDACTED`] when the request is printed
+/// with [`Debug`]. The values of headers that carry credentials or sessions are zeroed when the
+/// request is dropped, like the body.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+
+    /// Header names and values in the order they were added
+    pub headers: Vec<(String, String)>,
+
+    /// Kept as a [`Secret`] since it can contain a password, see [`Request::form`]
+    pub body: Option<Secret>,
+
+    /// The [`crate::types::SchoolListing::url_name`] of the school the request is for, used to
+    /// pick the rate limit, see [`RateLimiter`]
+    pub school: Option<String>,
+
+    /// The endpoint the request is for, used to cache the response, see [`Cache`]
+    pub endpoint: Option<Endpoint>,
+
+    /// Id of the user the request is made for, responses are only cached for the same user
+    pub user: Option<u32>,
+}
+
+/// A response with the entire body read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: StatusCode,
+
+    /// Header names and values in the order they were received
+    pub headers: Vec<(String, String)>,
+
+    pub body: String,
+}
+
+/// Sends requests for a [`crate::Client`] and every [`crate::User`] it creates
+#[derive(Debug, Clone)]
+pub(crate) struct Http {
+    transport: Arc<dyn HttpTransport>,
+    retry: Arc<RetryPolicy>,
+    limiter: Arc<RateLimiter>,
+    cache: Option<Cache>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    config: Arc<HttpConfig>,
+
+    /// Used for `Retry-After` dates
+    clock: Arc<dyn Clock>,
+}
+
+impl Request {
+    /// Create a request without any headers or body
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+            school: None,
+            endpoint: None,
+            user: None,
+        }
+    }
+
+    /// Create a GET request
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(Method::GET, url)
+    }
+
+    /// Create a POST request
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(Method::POST, url)
+    }
+
+    /// Add a header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Get the value of the first header called `name`, ignoring case
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Set the school the request is for
+    pub fn school(mut self, school: impl Into<String>) -> Self {
+        self.school = Some(school.into());
+        self
+    }
+
+    /// Set the endpoint the request is for
+    pub fn endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Set the id of the user the request is made for
+    pub fn user(mut self, user: u32) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Set the body to `form` encoded as `application/x-www-form-urlencoded`
+    ///
+    /// The encoded form goes straight into a [`Secret`], so it is zeroed once the request and
+    /// every clone of it, like the ones made for retries, are dropped.
+    ///
+    /// # Panics
+    /// If `form` can't be url encoded, which doesn't happen for a list of string pairs.
+    pub fn form(self, form: &impl Serialize) -> Self {
+        let body = serde_urlencoded::to_string(form).expect("form should be url encodable");
+
+        let mut request = self.header("content-type", "application/x-www-form-urlencoded");
+        request.body = Some(Secret::from(body));
+        request
+    }
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        for (name, value) in &mut self.headers {
+            if is_secret_header(name) {
+                value.zeroize();
+            }
+        }
+    }
+}
+
+impl Debug for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Request")
+            .field("method", &self.method)
+            .field("url", &redact_query(&self.url))
+            .field("headers", &redact_headers(&self.headers))
+            .field(
+                "body",
+                &self.body.as_ref().map(|body| redact_form(body.expose())),
+            )
+            .field("school", &self.school)
+            .field("endpoint", &self.endpoint)
+            .field("user", &self.user)
+            .finish()
+    }
+}
+
+/// Replace the values of [`SECRET_HEADERS`] with [`REDACTED`]
+pub(crate) fn redact_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if is_secret_header(name) {
+                (name.clone(), REDACTED.to_string())
+            } else {
+                (name.clone(), value.clone())
+            }
+        })
+        .collect()
+}
+
+/// Check if `name` is one of the [`SECRET_HEADERS`]
+pub(crate) fn is_secret_header(name: &str) -> bool {
+    SECRET_HEADERS
+        .iter()
+        .any(|secret| name.eq_ignore_ascii_case(secret))
+}
+
+/// Replace the values of [`SECRET_FORM_FIELDS`] in a url encoded form with [`REDACTED`]
+///
+/// Works on the encoded form so the secret is never decoded into another string. Bodies that
+/// aren't forms are kept as they are.
+pub(crate) fn redact_form(body: &str) -> String {
+    redact_pairs(body, &SECRET_FORM_FIELDS)
+}
+
+/// Replace the values of [`SECRET_QUERY_PARAMETERS`] in the query of `url` with [`REDACTED`]
+pub(crate) fn redact_query(url: &str) -> String {
+    let Some((base, rest)) = url.split_once('?') else {
+        return url.to_string();
+    };
+
+    match rest.split_once('#') {
+        Some((query, fragment)) => format!(
+            "{}?{}#{}",
+            base,
+            redact_pairs(query, &SECRET_QUERY_PARAMETERS),
+            fragment
+        ),
+        None => format!("{}?{}", base, redact_pairs(rest, &SECRET_QUERY_PARAMETERS)),
+    }
+}
+
+/// Replace the values of `secrets` in url encoded `name=value` pairs, ignoring case
+fn redact_pairs(pairs: &str, secrets: &[&str]) -> String {
+    let is_secret = |name: &str| {
+        secrets
+            .iter()
+            .any(|secret| name.eq_ignore_ascii_case(secret))
+    };
+
+    pairs
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if is_secret(name) => {
+                serde_urlencoded::to_string([(name, REDACTED)]).unwrap_or_default()
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+impl Response {
+    /// Create a response from a cached one
+    fn cached(entry: &CacheEntry) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body: entry.body.clone(),
+        }
+    }
+
+    /// Get the value of the first header called `name`, ignoring case
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Self::new(
+            Arc::new(ReqwestTransport::default()),
+            RetryPolicy::default(),
+            RateLimiter::default(),
+            None,
+            Vec::new(),
+            HttpConfig::default(),
+            Arc::new(SystemClock),
+        )
+    }
+}
+
+impl Http {
+    pub(crate) fn new(
+        transport: Arc<dyn HttpTransport>,
+        retry: RetryPolicy,
+        limiter: RateLimiter,
+        cache: Option<Cache>,
+        middleware: Vec<Arc<dyn Middleware>>,
+        config: HttpConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            transport,
+            retry: Arc::new(retry),
+            limiter: Arc::new(limiter),
+            cache,
+            middleware,
+            config: Arc::new(config),
+            clock,
+        }
+    }
+
+    /// Same as this one but sending requests with `transport`
+    pub(crate) fn with_transport(&self, transport: Arc<dyn HttpTransport>) -> Self {
+        Self {
+            transport,
+            ..self.clone()
+        }
+    }
+
+    /// Get the settings used to create http clients, see [`HttpConfig`]
+    pub(crate) fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    /// Get the cache, if there is one
+    pub(crate) fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Send a request, answering from the [`Cache`] if possible
+    ///
+    /// A fresh cached response is returned without sending anything. An expired one is sent with
+    /// `If-None-Match` and `If-Modified-Since` so schoolsoft can answer with `304 Not Modified`
+    /// instead of the entire body.
+    ///
+    /// # Returns
+    /// The response if it had a successful status code, otherwise the error from the last attempt.
+    pub(crate) async fn send(&self, mut request: Request) -> Result<Response, RequestError> {
+        let Some((cache, key)) = self.cache_key(&request) else {
+            return self.send_retrying(&request).await;
+        };
+
+        let cached = cache.get(&key);
+
+        if let Some((entry, fresh)) = &cached {
+            if *fresh {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(endpoint = %key.endpoint, "answered from cache");
+
+                return Ok(Response::cached(entry));
+            }
+
+            if let Some(etag) = &entry.etag {
+                request = request.header("if-none-match", etag.as_str());
+            }
+
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header("if-modified-since", last_modified.as_str());
+            }
+        }
+
+        match (self.send_retrying(&request).await, cached) {
+            (Err(RequestError::UncheckedCode(StatusCode::NOT_MODIFIED, _)), Some((entry, _))) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(endpoint = %key.endpoint, "cached response not modified");
+
+                let response = Response::cached(&entry);
+                cache.put(&key, entry);
+                Ok(response)
+            }
+            (Ok(response), _) => {
+                cache.put(
+                    &key,
+                    CacheEntry {
+                        body: response.body.clone(),
+                        stored: cache.clock.now(),
+                        etag: response.header_value("etag").map(str::to_string),
+                        last_modified: response.header_value("last-modified").map(str::to_string),
+                    },
+                );
+                Ok(response)
+            }
+            (Err(error), _) => Err(error),
+        }
+    }
+
+    /// Get a fresh cached response to `request` without sending anything
+    ///
+    /// Lets callers skip work that is only needed when the request is actually sent, like getting
+    /// a token.
+    pub(crate) fn cached(&self, request: &Request) -> Option<Response> {
+        let (cache, key) = self.cache_key(request)?;
+
+        match cache.get(&key)? {
+            (entry, true) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(endpoint = %key.endpoint, "answered from cache");
+
+                Some(Response::cached(&entry))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the cache and the key to cache the response to `request` under, if it should be cached
+    fn cache_key(&self, request: &Request) -> Option<(&Cache, CacheKey)> {
+        let cache = self.cache.as_ref()?;
+        let endpoint = request.endpoint?;
+
+        if request.method != Method::GET || !cache.is_enabled(endpoint) {
+            return None;
+        }
+
+        let key = CacheKey {
+            endpoint,
+            user: request.user,
+            url: request.url.clone(),
+        };
+
+        Some((cache, key))
+    }
+
+    /// Send a request, retrying according to the [`RetryPolicy`]
+    ///
+    /// Every attempt passes through the [`Middleware`] and, unless a middleware answers it, has to
+    /// fit within the [`RateLimiter`].
+    async fn send_retrying(&self, request: &Request) -> Result<Response, RequestError> {
+        let mut attempt = 1;
+
+        loop {
+            let attempt_result =
+                instrument::request(request, self.send_through_middleware(request)).await;
+
+            let (error, retry_after) = match attempt_result {
+                Ok(response) => match check_codes(response.status, &response.body) {
+                    Ok(()) => return Ok(response),
+                    Err(error) => {
+                        let retry_after = response.header_value("retry-after").map(str::to_string);
+                        (error, retry_after)
+                    }
+                },
+                Err(error) => (error, None),
+            };
+
+            if attempt >= self.retry.max_attempts || !self.retry.is_retryable(&error) {
+                return Err(error);
+            }
+
+            let delay = retry_after
+                .as_deref()
+                .and_then(|value| self.retry.retry_after(value, self.clock.now_utc()))
+                .unwrap_or_else(|| self.retry.backoff_for(attempt));
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempt, status = ?error.status(), ?delay, "retrying request");
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Send a request a single time, without retrying or caching
+    ///
+    /// Used for requests that change something, where sending them again could do it twice.
+    #[cfg(feature = "unverified")]
+    pub(crate) async fn send_once(&self, request: Request) -> Result<Response, RequestError> {
+        let response =
+            instrument::request(&request, self.send_through_middleware(&request)).await?;
+
+        check_codes(response.status, &response.body)?;
+        Ok(response)
+    }
+
+    /// Same as [`Self::send`] but only returns the body
+    pub(crate) async fn text(&self, request: Request) -> Result<String, RequestError> {
+        self.send(request).await.map(|response| response.body)
+    }
+
+    /// Send a request once through every [`Middleware`] without looking at the status code
+    ///
+    /// Only requests that are actually sent have to fit within the [`RateLimiter`].
+    async fn send_through_middleware(&self, request: &Request) -> Result<Response, RequestError> {
+        let mut request = request.clone();
+        let mut synthetic = None;
+        let mut ran = 0;
+
+        for middleware in &self.middleware {
+            ran += 1;
+            synthetic = middleware.on_request(&mut request);
+
+            if synthetic.is_some() {
+                break;
+            }
+        }
+
+        let mut response = match synthetic {
+            Some(response) => response,
+            None => {
+                self.limiter.acquire(request.school.as_deref()).await?;
+                self.transport.send(&request).await?
+            }
+        };
+
+        for middleware in self.middleware[..ran].iter().rev() {
+            middleware.on_response(&request, &mut response);
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn debug_hides_secrets() {
+        let request = Request::post("https://sms.schoolsoft.se/mock_school/rest/app/login")
+            .header("appkey", "123notreal")
+            .header("Token", "one_of_those_tokens")
+            .form(&[("identification", "user"), ("verification", "hunter2")]);
+
+        let debug = format!("{:?}", request);
+        assert!(!debug.contains("123notreal"));
+        assert!(!debug.contains("one_of_those_tokens"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("identification=user"));
+    }
+
+    #[test]
+    fn redacts_form() {
+        assert_eq!(
+            redact_form("identification=user&verification=hunter2&logintype=4"),
+            "identification=user&verification=%3Credacted%3E&logintype=4"
+        );
+        assert_eq!(redact_form("not a form"), "not a form");
+    }
+
+    #[test]
+    fn redacts_query() {
+        assert_eq!(
+            redact_query(
+                "https://sms1.schoolsoft.se/s/jsp/app/TokenLogin.jsp?token=abc&orgid=1#top"
+            ),
+            "https://sms1.schoolsoft.se/s/jsp/app/TokenLogin.jsp?token=%3Credacted%3E&orgid=1#top"
+        );
+        assert_eq!(
+            redact_query("https://sms.schoolsoft.se/s/api/lessons/student/1"),
+            "https://sms.schoolsoft.se/s/api/lessons/student/1"
+        );
+    }
+}
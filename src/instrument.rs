@@ -0,0 +1,107 @@
+//! Helpers for the spans recorded with the `tracing` feature
+//!
+//! Spans never record function arguments, only the fields listed where they are created, so app
+//! keys, tokens and passwords can't end up in the output. Urls are recorded without their query
+//! string and parse errors without their message, as both can contain parts of a secret.
+//!
+//! Without the feature everything here compiles down to calling the wrapped code directly.
+
+use std::future::Future;
+
+use crate::{
+    http::{Request, Response},
+    types::error::RequestError,
+};
+
+/// Run one attempt at sending `request` inside a span
+///
+/// The status code, latency and size of the response are added to the span once it is done.
+pub(crate) async fn request(
+    request: &Request,
+    send: impl Future<Output = Result<Response, RequestError>>,
+) -> Result<Response, RequestError> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::{field, Instrument};
+
+        let span = tracing::debug_span!(
+            "request",
+            method = %request.method,
+            url = redact_url(&request.url),
+            school = request.school.as_deref(),
+            endpoint = request.endpoint.map(field::display),
+            status = field::Empty,
+            latency_ms = field::Empty,
+            size = field::Empty,
+        );
+
+        let started = std::time::Instant::now();
+        let result = send.instrument(span.clone()).await;
+
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status.as_u16());
+                span.record("size", response.body.len());
+            }
+            Err(error) => {
+                if let Some(status) = error.status() {
+                    span.record("status", status.as_u16());
+                }
+            }
+        }
+
+        result
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = request;
+        send.await
+    }
+}
+
+/// Remove the query string and fragment from a url
+#[cfg(feature = "tracing")]
+pub(crate) fn redact_url(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Run the parse step for `endpoint` inside a span
+pub(crate) fn parse<T, E>(
+    endpoint: &'static str,
+    parse: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("parse", endpoint).entered();
+
+    let result = parse();
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = endpoint;
+
+    #[cfg(feature = "tracing")]
+    if result.is_err() {
+        tracing::warn!(endpoint, "failed to parse response");
+    }
+
+    result
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_query() {
+        assert_eq!(
+            redact_url("https://sms.schoolsoft.se/school/jsp/app/TokenLogin.jsp?token=secret"),
+            "https://sms.schoolsoft.se/school/jsp/app/TokenLogin.jsp"
+        );
+        assert_eq!(
+            redact_url("https://sms.schoolsoft.se/school"),
+            "https://sms.schoolsoft.se/school"
+        );
+    }
+}
@@ -1,22 +1,57 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::{Cache, Endpoint};
+use crate::clock::{Clock, SystemClock};
+use crate::credentials::CredentialProvider;
+use crate::device::DeviceProfile;
+use crate::http::{Http, Request};
+use crate::middleware::Middleware;
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
 use crate::types::error;
 use crate::types::SchoolListing;
-use crate::types::User;
-use crate::deserializers::Deserializer;
+use crate::types::{User, UserType};
+use crate::secret::Secret;
+use crate::session::SessionStore;
+use crate::transport::{HttpConfig, HttpTransport, ReqwestTransport};
+
+pub use crate::types::error::{Error, ErrorKind};
 
 #[cfg(feature = "serialize")]
 pub mod serializers;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(feature = "unverified")]
+pub mod report;
+
+pub mod cache;
+pub mod cassette;
+pub mod clock;
+pub mod credentials;
 pub mod deserializers;
+pub mod device;
+pub mod http;
+mod instrument;
+pub mod middleware;
+pub mod rate_limit;
+pub mod retry;
+pub mod schedule;
+pub mod secret;
+pub mod session;
+pub mod transport;
 pub mod types;
 pub mod user;
 pub mod utils;
-pub mod schedule;
+pub mod web;
 
 /// Api client for the api used by schoolsofts app
 #[derive(Debug)]
 pub struct Client {
-    /// Http client used for requests.
-    client: reqwest::Client,
+    /// Sends all requests, shared with every user created by this client.
+    http: Http,
 
     /// Url to put before all requests.
     /// Default: <https://sms.schoolsoft.se>
@@ -24,13 +59,20 @@ pub struct Client {
     /// Can be modified for testing purposes.
     base_url: String,
 
-    /// Device id to use when requesting a token.
-    /// Default: ""
+    /// Device identity sent with every request, given to every user created by this client.
+    /// Default: [`DeviceProfile::default`]
     ///
     /// Requesting a token requires a device id. The id can be "" without causing any known issues.
     ///
     /// Its presumablly used by schoolsoft for logging and analytics.
-    device_id: String,
+    device: DeviceProfile,
+
+    /// Given to every user created by this client, see [`credentials`]
+    credentials: Option<Arc<dyn CredentialProvider>>,
+
+    /// Source of the current time, given to every user created by this client.
+    /// Default: [`SystemClock`]
+    clock: Arc<dyn Clock>,
 
     /// Some(user) if the client is logged in.
     /// None if the client is not logged in.
@@ -100,7 +142,16 @@ pub struct Client {
 #[derive(Default)]
 pub struct ClientBuilder {
     base_url: Option<String>,
-    device_id: Option<String>,
+    device: Option<DeviceProfile>,
+    retry: Option<RetryPolicy>,
+    rate_limit: Option<RateLimiter>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    http_client: Option<reqwest::Client>,
+    http_config: HttpConfig,
+    cache: Option<Cache>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    credentials: Option<Arc<dyn CredentialProvider>>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl Client {
@@ -119,9 +170,6 @@ impl Client {
     ///
     /// ```
     /// # use schoolsoft::ClientBuilder;
-    /// # use tokio::test;
-    ///
-    /// # #[test]
     /// # async fn login() {
     /// let mut client = ClientBuilder::new()
     ///    .build();
@@ -133,34 +181,123 @@ impl Client {
     pub async fn login(
         &mut self,
         username: &str,
-        password: &str,
+        password: impl Into<Secret>,
+        school: &str,
+    ) -> Result<(), error::LoginError> {
+        self.login_as(username, password, school, UserType::Student)
+            .await
+    }
+
+    /// Same as [`Self::login`] but for parents and teachers
+    ///
+    /// With the `unverified` feature, parents have to select one of their children before getting
+    /// anything child specific, see `User::get_children` and `User::select_child`. Without it
+    /// parents can login, but every request for data fails with
+    /// [`error::RequestError::ParentsUnsupported`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, types::UserType};
+    /// # async fn login() {
+    /// let mut client = ClientBuilder::new()
+    ///    .build();
+    ///
+    /// client.login_as("username", "password", "school", UserType::Parent).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn login_as(
+        &mut self,
+        username: &str,
+        password: impl Into<Secret>,
         school: &str,
+        user_type: UserType,
     ) -> Result<(), error::LoginError> {
-        // Construct url
         let school_url = format!("{}/{}", self.base_url, school);
-        let url = format!("{}/rest/app/login", school_url);
+        let password = password.into();
+        let user = User::login(
+            &self.http,
+            &self.device,
+            &school_url,
+            username,
+            &password,
+            user_type,
+        )
+        .await?;
 
-        // Construct body
-        let mut params = std::collections::HashMap::new();
-        params.insert("identification", username);
-        params.insert("verification", password);
-        params.insert("logintype", "4");
-        params.insert("usertype", "1");
+        self.user = Some(self.adopt(user));
+        Ok(())
+    }
 
-        let request = self
-            .client
-            .request(reqwest::Method::POST, url)
-            .form(&params);
+    /// Login to a school from [`Self::schools`]
+    ///
+    /// Checks that the school allows `user_type` to login through the app before sending any
+    /// requests, see [`types::LoginMethods::supports_app`].
+    ///
+    /// # Errors
+    /// [`error::LoginError::AppLoginUnsupported`] if the school doesn't allow app logins for
+    /// `user_type`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, types::UserType};
+    /// # async fn login() {
+    /// let mut client = ClientBuilder::new()
+    ///    .build();
+    ///
+    /// let schools = client.schools().await.unwrap();
+    /// let school = schools.iter().find(|school| school.url_name == "school").unwrap();
+    ///
+    /// client.login_to(school, "username", "password", UserType::Student).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn login_to(
+        &mut self,
+        school: &SchoolListing,
+        username: &str,
+        password: impl Into<Secret>,
+        user_type: UserType,
+    ) -> Result<(), error::LoginError> {
+        if !school.login_methods.supports_app(user_type) {
+            return Err(error::LoginError::AppLoginUnsupported(user_type));
+        }
 
-        let data = utils::make_request(request)
+        self.login_as(username, password, &school.url_name, user_type)
             .await
-            .map_err(error::LoginError::RequestError)?;
+    }
 
-        // Parse response
-        let user = User::deserialize(&data).map_err(error::LoginError::ParseError)?;
+    /// Restore a previously saved user from a [`SessionStore`].
+    ///
+    /// If the store contains a session, [`Self::user`] is replaced with the restored user, no
+    /// request is made to schoolsoft. The stored token is reused until it is close to expiring.
+    ///
+    /// # Returns
+    /// `true` if a session was restored, `false` if the store was empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use schoolsoft::ClientBuilder;
+    /// # use schoolsoft::session::MemorySessionStore;
+    /// let store = MemorySessionStore::new();
+    /// let mut client = ClientBuilder::new().build();
+    ///
+    /// assert!(!client.restore_session(&store).unwrap());
+    /// assert!(client.user.is_none());
+    /// ```
+    pub fn restore_session(
+        &mut self,
+        store: &impl SessionStore,
+    ) -> Result<bool, error::SessionError> {
+        let Some(session) = store.load()? else {
+            return Ok(false);
+        };
 
-        self.user = Some(user);
-        Ok(())
+        let user = session.restore()?;
+
+        self.user = Some(self.adopt(user));
+        Ok(true)
     }
 
     /// Get list of schools.
@@ -170,23 +307,40 @@ impl Client {
     /// # Examples
     /// ```
     /// # use schoolsoft::ClientBuilder;
-    /// # use tokio::test;
-    /// #
-    /// # #[test]
     /// # async fn schools() {
     /// let client = ClientBuilder::new()
     ///   .build();
     ///
     /// let schools = client.schools().await;
     /// # }
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn schools(&self) -> Result<Vec<SchoolListing>, error::SchoolListingError> {
         let url = format!("{}/rest/app/schoollist/prod", self.base_url);
 
-        let response = utils::make_request(self.client.get(&url))
+        let response = self
+            .http
+            .text(self.device.apply(Request::get(url).endpoint(Endpoint::Schools)))
             .await
             .map_err(error::SchoolListingError::RequestError)?;
 
-        SchoolListing::deserialize_many(&response)
+        instrument::parse("schools", || SchoolListing::deserialize_many(&response))
+    }
+
+    /// Give a user the same configuration as the client
+    ///
+    /// Users created by logging in or restoring a session get this automatically. Use it for users
+    /// created some other way, like with [`User::new`], so they share the client's connections,
+    /// retry policy and rate limit.
+    pub fn adopt(&self, mut user: User) -> User {
+        user.http = self.http.clone();
+        user.device = self.device.clone();
+        user.credentials = self.credentials.clone();
+        user.with_clock(self.clock.clone())
+    }
+
+    /// Get the response cache, if one was set with [`ClientBuilder::cache`].
+    pub fn cache(&self) -> Option<&Cache> {
+        self.http.cache()
     }
 
     /// Get the base url.
@@ -215,7 +369,17 @@ impl Client {
     ///
     /// assert_eq!(client.device_id(), "1234567890");
     pub fn device_id(&self) -> &str {
-        self.device_id.as_ref()
+        &self.device.device_id
+    }
+
+    /// Get the device profile.
+    pub fn device(&self) -> &DeviceProfile {
+        &self.device
+    }
+
+    /// Get the clock.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
     }
 }
 
@@ -257,19 +421,281 @@ impl ClientBuilder {
     /// assert_eq!(client.device_id(), "1234567890");
     /// ```
     pub fn device_id(mut self, device_id: String) -> Self {
-        self.device_id = Some(device_id);
+        self.device.get_or_insert_with(DeviceProfile::default).device_id = device_id;
         self
     }
 
+    /// Set the entire [`DeviceProfile`].
+    ///
+    /// Replaces any device id set with [`Self::device_id`].
+    ///
+    /// Default: [`DeviceProfile::default`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, device::DeviceProfile};
+    /// let client = ClientBuilder::new()
+    ///   .device(DeviceProfile {
+    ///       device_id: "1234567890".to_string(),
+    ///       os: "ios".to_string(),
+    ///       app_version: "2.3.14".to_string(),
+    ///       user_agent: "SchoolSoft/2.3.14".to_string(),
+    ///   })
+    ///   .build();
+    ///
+    /// assert_eq!(client.device().os, "ios");
+    /// ```
+    pub fn device(mut self, device: DeviceProfile) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// Set the [`RetryPolicy`] used for every request made by the client and its users.
+    ///
+    /// Default: [`RetryPolicy::none`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, retry::RetryPolicy};
+    /// let client = ClientBuilder::new()
+    ///   .retry(RetryPolicy::new().max_attempts(5))
+    ///   .build();
+    /// ```
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set the [`RateLimiter`] shared by the client and every user it creates.
+    ///
+    /// Default: no limit
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, rate_limit::{RateLimit, RateLimiter}};
+    /// let client = ClientBuilder::new()
+    ///   .rate_limit(RateLimiter::new().limit(RateLimit::per_second(10)))
+    ///   .build();
+    /// ```
+    pub fn rate_limit(mut self, rate_limit: RateLimiter) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the longest time to wait for a connection to be established.
+    ///
+    /// Default: no timeout
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http_config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the longest time a request can take, from connecting until the entire body is read.
+    ///
+    /// Each retry gets the full time again, see [`Self::retry`].
+    ///
+    /// Default: no timeout
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use schoolsoft::ClientBuilder;
+    /// let client = ClientBuilder::new()
+    ///   .connect_timeout(Duration::from_secs(5))
+    ///   .timeout(Duration::from_secs(30))
+    ///   .build();
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http_config.timeout = Some(timeout);
+        self
+    }
+
+    /// Add a proxy to send requests through.
+    ///
+    /// Can be called several times, the first proxy that matches a request is used.
+    ///
+    /// Default: the proxies in the system environment variables, like `HTTPS_PROXY`
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::ClientBuilder;
+    /// let client = ClientBuilder::new()
+    ///   .proxy(reqwest::Proxy::https("http://proxy.example.com:8080").unwrap())
+    ///   .build();
+    /// ```
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.http_config.proxies.push(proxy);
+        self
+    }
+
+    /// Trust a certificate in addition to the system ones, like the one of an inspecting proxy.
+    ///
+    /// Can be called several times.
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.http_config.root_certificates.push(certificate);
+        self
+    }
+
+    /// Set the user agent sent with every request.
+    ///
+    /// Same as setting [`DeviceProfile::user_agent`], replaced by [`Self::device`].
+    ///
+    /// Default: the user agent of [`DeviceProfile::default`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::ClientBuilder;
+    /// let client = ClientBuilder::new()
+    ///   .user_agent("my-tool/1.0")
+    ///   .build();
+    ///
+    /// assert_eq!(client.device().user_agent, "my-tool/1.0");
+    /// ```
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.device.get_or_insert_with(DeviceProfile::default).user_agent = user_agent.into();
+        self
+    }
+
+    /// Use a pre-built [`reqwest::Client`] to send requests to the app api.
+    ///
+    /// The timeouts, proxies and root certificates set on this builder are then only used for
+    /// [`web::WebSession`]s, which need a client that keeps cookies. Replaced by
+    /// [`Self::transport`].
+    ///
+    /// Default: a client created from the settings on this builder
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Set the [`HttpTransport`] used to send every request made by the client and its users.
+    ///
+    /// Default: [`ReqwestTransport`]
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Set the response [`Cache`] shared by the client and every user it creates.
+    ///
+    /// The cache uses the client's [`Clock`] to decide when responses have expired.
+    ///
+    /// Default: nothing is cached
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{ClientBuilder, cache::Cache};
+    /// let client = ClientBuilder::new()
+    ///   .cache(Cache::memory())
+    ///   .build();
+    ///
+    /// assert!(client.cache().is_some());
+    /// ```
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Add a [`Middleware`] that runs around every request made by the client and its users.
+    ///
+    /// Can be called several times, requests pass through the middleware in the order they were
+    /// added and responses in the opposite order.
+    ///
+    /// Default: no middleware
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Set a [`CredentialProvider`] used to login again if schoolsoft stops accepting the app key.
+    ///
+    /// Every user created by the client, by logging in or restoring a session, gets the provider.
+    ///
+    /// Default: None
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::ClientBuilder;
+    /// # use schoolsoft::credentials::Credentials;
+    /// let client = ClientBuilder::new()
+    ///   .credentials(Credentials::new("username", "password"))
+    ///   .build();
+    /// ```
+    pub fn credentials(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+
+    /// Set the [`Clock`] used for everything that depends on the current time.
+    ///
+    /// Every user created by the client uses the same clock, so it decides when tokens need to be
+    /// refreshed, which year the weeks in a schedule belong to and how long to wait for a
+    /// `Retry-After` date.
+    ///
+    /// Default: [`SystemClock`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::ClientBuilder;
+    /// # use schoolsoft::clock::{Clock, FixedClock};
+    /// let now = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    /// let client = ClientBuilder::new()
+    ///   .clock(FixedClock::new(now))
+    ///   .build();
+    ///
+    /// assert_eq!(client.clock().now(), now);
+    /// ```
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Build the client.
+    ///
+    /// # Panics
+    /// If the http client can't be created, like when a root certificate is invalid. Use
+    /// [`Self::try_build`] to handle the error instead.
     pub fn build(self) -> Client {
-        Client {
-            client: reqwest::Client::new(),
+        self.try_build().expect("should be able to create the http client")
+    }
+
+    /// Build the client, failing if the http client can't be created.
+    ///
+    /// # Errors
+    /// If reqwest can't create the http client, see [`HttpConfig::client`]. Never fails when a
+    /// [`Self::transport`] or [`Self::http_client`] is set.
+    pub fn try_build(self) -> Result<Client, reqwest::Error> {
+        let clock = self.clock.unwrap_or(Arc::new(SystemClock));
+
+        let cache = self.cache.map(|mut cache| {
+            cache.clock = clock.clone();
+            cache
+        });
+
+        let transport: Arc<dyn HttpTransport> = match (self.transport, self.http_client) {
+            (Some(transport), _) => transport,
+            (None, Some(client)) => Arc::new(ReqwestTransport::new(client)),
+            (None, None) => Arc::new(ReqwestTransport::new(self.http_config.client()?)),
+        };
+
+        Ok(Client {
+            http: Http::new(
+                transport,
+                self.retry.unwrap_or_default(),
+                self.rate_limit.unwrap_or_default(),
+                cache,
+                self.middleware,
+                self.http_config,
+                clock.clone(),
+            ),
             base_url: self
                 .base_url
                 .unwrap_or("https://sms.schoolsoft.se".to_string()),
-            device_id: self.device_id.unwrap_or("".to_string()),
+            device: self.device.unwrap_or_default(),
+            credentials: self.credentials,
+            clock,
             user: None,
-        }
+        })
     }
 }
 
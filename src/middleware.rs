@@ -0,0 +1,54 @@
+//! Observing and changing requests before they are sent and responses before they are used
+//!
+//! Every request to the app api passes through the [`Middleware`] added with
+//! [`crate::ClientBuilder::middleware`], in the order they were added. Responses pass through them
+//! in the opposite order. This is the place for logging, metrics, adding headers or simulating
+//! failures without changing the library.
+//!
+//! Middleware runs once for every attempt that would reach schoolsoft. Responses answered by the
+//! [`crate::cache::Cache`] without asking schoolsoft don't pass through it.
+
+use std::fmt::Debug;
+
+use crate::http::{Request, Response};
+
+/// Something that runs around every request
+///
+/// Both methods do nothing by default, so only the one that is needed has to be implemented.
+///
+/// # Examples
+/// ```
+/// # use schoolsoft::{ClientBuilder, http::{Request, Response}, middleware::Middleware};
+/// #[derive(Debug)]
+/// struct Tag;
+///
+/// impl Middleware for Tag {
+///     fn on_request(&self, request: &mut Request) -> Option<Response> {
+///         request.headers.push(("x-request-source".to_string(), "my-service".to_string()));
+///         None
+///     }
+/// }
+///
+/// let client = ClientBuilder::new()
+///     .middleware(Tag)
+///     .build();
+/// ```
+pub trait Middleware: Debug + Send + Sync {
+    /// Called before a request is sent
+    ///
+    /// # Returns
+    /// `Some(response)` to answer the request without sending it. The response is treated as if
+    /// it came from schoolsoft, so its status code is checked and it may be retried. Middleware
+    /// added after this one is skipped.
+    fn on_request(&self, request: &mut Request) -> Option<Response> {
+        let _ = request;
+        None
+    }
+
+    /// Called with every response before its status code is checked
+    ///
+    /// Not called if the request couldn't be sent or the response couldn't be read.
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let _ = (request, response);
+    }
+}
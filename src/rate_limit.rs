@@ -0,0 +1,271 @@
+//! Limiting how fast requests are sent to schoolsoft
+//!
+//! A [`RateLimiter`] set with [`crate::ClientBuilder::rate_limit`] is shared by the client and
+//! every user it creates, so a service making requests for many users at once stays under a
+//! single limit. Limits are token buckets: a number of requests can be sent at once, after that
+//! new requests are allowed at a steady pace.
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::types::error::RequestError;
+
+/// A token bucket
+///
+/// Up to [`Self::burst`] requests can be sent at once, after that one more is allowed every
+/// [`Self::interval`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How many requests can be sent at once
+    pub burst: u32,
+
+    /// How long it takes for one more request to be allowed
+    pub interval: Duration,
+}
+
+/// Rate limits for all requests and for requests to specific schools
+///
+/// A request has to fit within both the overall limit and the limit of its school, if they are
+/// set. Requests that don't belong to a school, like getting the school list, only count towards
+/// the overall limit.
+///
+/// By default requests wait until they fit, see [`Self::wait`].
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use schoolsoft::{ClientBuilder, rate_limit::{RateLimit, RateLimiter}};
+/// let client = ClientBuilder::new()
+///     .rate_limit(
+///         RateLimiter::new()
+///             .limit(RateLimit::per_second(20))
+///             .school("mock_school", RateLimit::new(5, Duration::from_secs(1))),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limit: Option<RateLimit>,
+    schools: HashMap<String, RateLimit>,
+    fail: bool,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    all: Option<Bucket>,
+    schools: HashMap<String, Bucket>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimit {
+    /// Allow `burst` requests at once and one more every `interval`
+    ///
+    /// # Panics
+    /// If `burst` is 0 or `interval` is zero.
+    pub fn new(burst: u32, interval: Duration) -> Self {
+        assert!(burst > 0, "burst must be at least 1");
+        assert!(!interval.is_zero(), "interval must not be zero");
+
+        Self { burst, interval }
+    }
+
+    /// Allow `requests` requests every second, all of them at once if needed
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn per_second(requests: u32) -> Self {
+        Self::per(requests, Duration::from_secs(1))
+    }
+
+    /// Allow `requests` requests every minute, all of them at once if needed
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::per(requests, Duration::from_secs(60))
+    }
+
+    /// Allow `requests` requests every `period`, never panicking
+    fn per(requests: u32, period: Duration) -> Self {
+        let requests = requests.max(1);
+        let interval = (period / requests).max(Duration::from_nanos(1));
+
+        Self::new(requests, interval)
+    }
+}
+
+impl RateLimiter {
+    /// Create a rate limiter without any limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit for all requests
+    pub fn limit(mut self, limit: RateLimit) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Set the limit for requests to a school
+    ///
+    /// `school` is the [`crate::types::SchoolListing::url_name`] of the school.
+    pub fn school(mut self, school: impl Into<String>, limit: RateLimit) -> Self {
+        self.schools.insert(school.into(), limit);
+        self
+    }
+
+    /// Set if requests should wait until they fit within the limits
+    ///
+    /// If `false` requests that don't fit fail with [`RequestError::RateLimited`] right away.
+    ///
+    /// Default: `true`
+    pub fn wait(mut self, wait: bool) -> Self {
+        self.fail = !wait;
+        self
+    }
+
+    /// Take room for one request to `school`, waiting until there is room if needed
+    ///
+    /// # Errors
+    /// [`RequestError::RateLimited`] if there is no room and the limiter doesn't wait.
+    pub async fn acquire(&self, school: Option<&str>) -> Result<(), RequestError> {
+        loop {
+            let wait = match self.try_acquire(school) {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+
+            if self.fail {
+                return Err(RequestError::RateLimited(wait));
+            }
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take room for one request if there is room in every limit that applies
+    ///
+    /// # Returns
+    /// How long to wait until there is room if there wasn't any
+    fn try_acquire(&self, school: Option<&str>) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets { all, schools } = &mut *buckets;
+
+        let mut limits = Vec::with_capacity(2);
+
+        if let Some(limit) = self.limit {
+            limits.push((limit, all.get_or_insert_with(|| Bucket::full(limit, now))));
+        }
+
+        if let Some((name, limit)) = school.and_then(|school| self.schools.get_key_value(school)) {
+            let bucket = schools
+                .entry(name.clone())
+                .or_insert_with(|| Bucket::full(*limit, now));
+            limits.push((*limit, bucket));
+        }
+
+        let mut wait = Duration::ZERO;
+        for (limit, bucket) in limits.iter_mut() {
+            bucket.refill(*limit, now);
+            wait = wait.max(bucket.wait(*limit));
+        }
+
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for (_, bucket) in limits {
+            bucket.tokens -= 1.0;
+        }
+
+        Ok(())
+    }
+}
+
+impl Bucket {
+    fn full(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now.duration_since(self.updated);
+        let added = elapsed.as_secs_f64() / limit.interval.as_secs_f64();
+
+        self.tokens = (self.tokens + added).min(limit.burst as f64);
+        self.updated = now;
+    }
+
+    /// How long until there is room for one request
+    fn wait(&self, limit: RateLimit) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+
+        limit.interval.mul_f64(1.0 - self.tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_zero() {
+        assert_eq!(RateLimit::per_second(0), RateLimit::per_second(1));
+        assert_eq!(
+            RateLimit::per_minute(0),
+            RateLimit::new(1, Duration::from_secs(60))
+        );
+        assert_eq!(
+            RateLimit::per_second(u32::MAX).interval,
+            Duration::from_nanos(1)
+        );
+    }
+
+    #[test]
+    fn burst_then_fail() {
+        let limiter = RateLimiter::new()
+            .limit(RateLimit::new(2, Duration::from_secs(60)))
+            .wait(false);
+
+        assert!(limiter.try_acquire(None).is_ok());
+        assert!(limiter.try_acquire(None).is_ok());
+
+        let wait = limiter.try_acquire(None).unwrap_err();
+        assert!(wait > Duration::from_secs(59));
+    }
+
+    #[test]
+    fn schools_are_separate() {
+        let limiter = RateLimiter::new()
+            .school("first", RateLimit::new(1, Duration::from_secs(60)))
+            .school("second", RateLimit::new(1, Duration::from_secs(60)));
+
+        assert!(limiter.try_acquire(Some("first")).is_ok());
+        assert!(limiter.try_acquire(Some("first")).is_err());
+        assert!(limiter.try_acquire(Some("second")).is_ok());
+
+        // Schools without a limit and requests without a school are never limited
+        assert!(limiter.try_acquire(Some("third")).is_ok());
+        assert!(limiter.try_acquire(None).is_ok());
+    }
+
+    #[test]
+    fn school_and_overall_limit() {
+        let limiter = RateLimiter::new()
+            .limit(RateLimit::new(1, Duration::from_secs(60)))
+            .school("first", RateLimit::new(5, Duration::from_secs(60)));
+
+        assert!(limiter.try_acquire(Some("first")).is_ok());
+        assert!(limiter.try_acquire(Some("first")).is_err());
+    }
+}
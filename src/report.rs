@@ -0,0 +1,346 @@
+//! Reporting absence
+//!
+//! Parents and students that are of age can report absence through the app, either for a range of
+//! days or for specific lessons. Build an [`AbsenceReport`] and send it with
+//! [`crate::User::report_absence`], which checks it against the schedule before anything is sent.
+//!
+//! Only available with the `unverified` feature. The app hasn't been seen reporting absence yet,
+//! so the url, the form and the response are guesses. Use [`AbsenceReport::dry_run`] to look at
+//! the request before letting it reach schoolsoft.
+//!
+//! # Examples
+//! ```no_run
+//! # use chrono::NaiveDate;
+//! # use schoolsoft::{report::{AbsenceReport, ReportOutcome}, user::User};
+//! # async fn run(user: User) {
+//! let schedule = user.get_schedule().await.unwrap();
+//!
+//! let sick = NaiveDate::from_ymd_opt(2024, 4, 3).unwrap();
+//! let report = AbsenceReport::days(sick, sick).reason("Sick");
+//!
+//! match user.report_absence(&report, &schedule).await.unwrap() {
+//!     ReportOutcome::Accepted(ids) => println!("Reported as {:?}", ids),
+//!     ReportOutcome::Rejected(message) => println!("Schoolsoft said no: {}", message),
+//!     ReportOutcome::DryRun(_) => unreachable!(),
+//! }
+//! # }
+//! ```
+
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+
+use crate::{
+    http::Request,
+    schedule::Schedule,
+    types::{error::ReportValidationError, User, UserType},
+};
+
+/// Absence to report, see [`crate::User::report_absence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbsenceReport {
+    /// What the absence covers
+    pub target: ReportTarget,
+
+    /// Reason shown to the school, like "Sick"
+    pub reason: Option<String>,
+
+    /// Validate and build the request without sending it, see [`ReportOutcome::DryRun`]
+    pub dry_run: bool,
+}
+
+/// What an [`AbsenceReport`] covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportTarget {
+    /// Every lesson from `from` until and including `to`
+    Days { from: NaiveDate, to: NaiveDate },
+
+    /// Specific lessons, as the date and the id of the [`crate::schedule::Occasion`]
+    Lessons(Vec<(NaiveDate, u64)>),
+}
+
+/// Status codes schoolsoft refuses a report with, returned as [`ReportOutcome::Rejected`] instead
+/// of an error
+pub(crate) const REJECTED_STATUSES: [StatusCode; 4] = [
+    StatusCode::BAD_REQUEST,
+    StatusCode::FORBIDDEN,
+    StatusCode::CONFLICT,
+    StatusCode::UNPROCESSABLE_ENTITY,
+];
+
+/// What happened to an [`AbsenceReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportOutcome {
+    /// Schoolsoft registered the absence, with the ids of the created absences
+    Accepted(Vec<u64>),
+
+    /// Schoolsoft refused to register the absence, with the message it gave
+    ///
+    /// Also returned when the report is refused with a `400`, `403`, `409` or `422` status code,
+    /// with the message from the body if it has one and otherwise the start of the body.
+    Rejected(String),
+
+    /// The report was valid but not sent because of [`AbsenceReport::dry_run`]
+    ///
+    /// Contains the request that would have been sent, without a token since getting one would
+    /// require sending a request.
+    DryRun(Request),
+}
+
+impl AbsenceReport {
+    /// Report absence for every lesson from `from` until and including `to`
+    pub fn days(from: NaiveDate, to: NaiveDate) -> Self {
+        Self {
+            target: ReportTarget::Days { from, to },
+            reason: None,
+            dry_run: false,
+        }
+    }
+
+    /// Report absence for specific lessons, given as the date and the
+    /// [`crate::schedule::Lesson::occasion_id`]
+    pub fn lessons(lessons: impl IntoIterator<Item = (NaiveDate, u64)>) -> Self {
+        Self {
+            target: ReportTarget::Lessons(lessons.into_iter().collect()),
+            reason: None,
+            dry_run: false,
+        }
+    }
+
+    /// Set the reason shown to the school
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Only validate and build the request, see [`ReportOutcome::DryRun`]
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Check that `user` can report the absence and that it matches `schedule`
+    ///
+    /// # Errors
+    /// - [`ReportValidationError::NotAllowed`] if the user is a teacher or a student that isn't of
+    ///   age
+    /// - [`ReportValidationError::InvalidRange`] if the range ends before it starts
+    /// - [`ReportValidationError::NoLessonsSelected`] if no lessons were given
+    /// - [`ReportValidationError::InPast`] for dates before today according to the users clock
+    /// - [`ReportValidationError::OutsideSchedule`] for dates outside of the schedule
+    /// - [`ReportValidationError::UnknownLesson`] if a lesson isn't in the schedule
+    /// - [`ReportValidationError::NoLessons`] if there are no lessons between the dates
+    pub fn validate(&self, user: &User, schedule: &Schedule) -> Result<(), ReportValidationError> {
+        match user.user_type {
+            UserType::Parent => (),
+            UserType::Student if user.is_of_age => (),
+            _ => return Err(ReportValidationError::NotAllowed),
+        }
+
+        let today = user.clock.today();
+
+        match &self.target {
+            ReportTarget::Days { from, to } => {
+                if to < from {
+                    return Err(ReportValidationError::InvalidRange(*from, *to));
+                }
+
+                if *from < today {
+                    return Err(ReportValidationError::InPast(*from));
+                }
+
+                let mut lessons = 0;
+
+                for date in from.iter_days().take_while(|date| date <= to) {
+                    let day = schedule
+                        .day(date)
+                        .ok_or(ReportValidationError::OutsideSchedule(date))?;
+
+                    lessons += day.lessons.len();
+                }
+
+                if lessons == 0 {
+                    return Err(ReportValidationError::NoLessons(*from, *to));
+                }
+            }
+            ReportTarget::Lessons(lessons) => {
+                if lessons.is_empty() {
+                    return Err(ReportValidationError::NoLessonsSelected);
+                }
+
+                for &(date, occasion_id) in lessons {
+                    if date < today {
+                        return Err(ReportValidationError::InPast(date));
+                    }
+
+                    let day = schedule
+                        .day(date)
+                        .ok_or(ReportValidationError::OutsideSchedule(date))?;
+
+                    if !day
+                        .lessons
+                        .iter()
+                        .any(|lesson| lesson.occasion_id == occasion_id)
+                    {
+                        return Err(ReportValidationError::UnknownLesson(date, occasion_id));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The form sent to schoolsoft
+    pub(crate) fn form(&self) -> Vec<(&'static str, String)> {
+        let mut form = match &self.target {
+            ReportTarget::Days { from, to } => vec![
+                ("fromDate", from.format("%Y-%m-%d").to_string()),
+                ("toDate", to.format("%Y-%m-%d").to_string()),
+            ],
+            ReportTarget::Lessons(lessons) => lessons
+                .iter()
+                .map(|(date, occasion_id)| {
+                    (
+                        "lesson",
+                        format!("{}:{}", date.format("%Y-%m-%d"), occasion_id),
+                    )
+                })
+                .collect(),
+        };
+
+        if let Some(reason) = &self.reason {
+            form.push(("reason", reason.clone()));
+        }
+
+        form
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::NaiveTime;
+    use pretty_assertions::assert_eq;
+    use reqwest::Url;
+
+    use super::*;
+    use crate::{clock::FixedClock, schedule::Lesson};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 4, day).unwrap()
+    }
+
+    fn user(user_type: UserType, is_of_age: bool) -> User {
+        let mut user = User::new(
+            Url::parse("https://sms.schoolsoft.se/mock_school").unwrap(),
+            "Mock User".to_string(),
+            "123notreal".to_string(),
+            user_type,
+            1337,
+            Vec::new(),
+        )
+        .with_clock(Arc::new(FixedClock::new(
+            date(2).and_hms_opt(7, 0, 0).unwrap(),
+        )));
+
+        user.is_of_age = is_of_age;
+        user
+    }
+
+    /// Schedule with a single lesson on wednesday the 3rd of april
+    fn schedule() -> Schedule {
+        let mut schedule = Schedule::from(date(1));
+        schedule.weeks[13].wednesday.lessons.push(Lesson {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            name: "Math".to_string(),
+            room: "A1".to_string(),
+            org_id: 1,
+            occasion_id: 1234,
+        });
+        schedule
+    }
+
+    #[test]
+    fn who_can_report() {
+        let report = AbsenceReport::days(date(3), date(3));
+
+        assert_eq!(
+            report.validate(&user(UserType::Parent, false), &schedule()),
+            Ok(())
+        );
+        assert_eq!(
+            report.validate(&user(UserType::Student, true), &schedule()),
+            Ok(())
+        );
+        assert_eq!(
+            report.validate(&user(UserType::Student, false), &schedule()),
+            Err(ReportValidationError::NotAllowed)
+        );
+        assert_eq!(
+            report.validate(&user(UserType::Teacher, true), &schedule()),
+            Err(ReportValidationError::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn days() {
+        let user = user(UserType::Parent, false);
+        let validate =
+            |from, to| AbsenceReport::days(date(from), date(to)).validate(&user, &schedule());
+
+        assert_eq!(validate(2, 5), Ok(()));
+        assert_eq!(
+            validate(5, 3),
+            Err(ReportValidationError::InvalidRange(date(5), date(3)))
+        );
+        assert_eq!(validate(1, 3), Err(ReportValidationError::InPast(date(1))));
+        assert_eq!(
+            validate(4, 5),
+            Err(ReportValidationError::NoLessons(date(4), date(5)))
+        );
+    }
+
+    #[test]
+    fn lessons() {
+        let user = user(UserType::Parent, false);
+        let validate = |lessons: Vec<(NaiveDate, u64)>| {
+            AbsenceReport::lessons(lessons).validate(&user, &schedule())
+        };
+
+        assert_eq!(validate(vec![(date(3), 1234)]), Ok(()));
+        assert_eq!(
+            validate(vec![]),
+            Err(ReportValidationError::NoLessonsSelected)
+        );
+        assert_eq!(
+            validate(vec![(date(3), 1)]),
+            Err(ReportValidationError::UnknownLesson(date(3), 1))
+        );
+        assert_eq!(
+            validate(vec![(date(4), 1234)]),
+            Err(ReportValidationError::UnknownLesson(date(4), 1234))
+        );
+
+        let outside = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        assert_eq!(
+            validate(vec![(outside, 1234)]),
+            Err(ReportValidationError::OutsideSchedule(outside))
+        );
+    }
+
+    #[test]
+    fn form() {
+        let report = AbsenceReport::lessons([(date(3), 1234), (date(3), 1235)]).reason("Sick");
+
+        assert_eq!(
+            report.form(),
+            vec![
+                ("lesson", "2024-04-03:1234".to_string()),
+                ("lesson", "2024-04-03:1235".to_string()),
+                ("reason", "Sick".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Retrying requests that failed for reasons that might go away
+//!
+//! Schoolsoft regularly answers with 500 during busy mornings. Set a [`RetryPolicy`] with
+//! [`crate::ClientBuilder::retry`] to have every request made by the client, and the users it
+//! creates, sent again after a short wait instead of failing right away.
+
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use reqwest::StatusCode;
+
+use crate::types::error::RequestError;
+
+/// When and how often to retry a failed request
+///
+/// The wait before each retry doubles for every attempt, starting at [`Self::initial_backoff`] and
+/// never going above [`Self::max_backoff`]. With [`Self::jitter`] enabled a random part of the
+/// wait is removed so that many clients don't retry at the same time. If the response includes a
+/// `Retry-After` header, that is used instead, up to [`Self::max_retry_after`].
+///
+/// The [`Default`] policy never retries, use [`RetryPolicy::new`] for sensible retries.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use schoolsoft::{ClientBuilder, retry::RetryPolicy};
+/// let client = ClientBuilder::new()
+///     .retry(
+///         RetryPolicy::new()
+///             .max_attempts(5)
+///             .backoff(Duration::from_millis(100), Duration::from_secs(5)),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times a request is sent at most, including the first attempt
+    pub max_attempts: u32,
+
+    /// Wait before the first retry
+    pub initial_backoff: Duration,
+
+    /// Longest wait between two attempts, not counting `Retry-After`
+    pub max_backoff: Duration,
+
+    /// Wait a random time between half and all of the backoff
+    pub jitter: bool,
+
+    /// Status codes that are retried
+    pub statuses: Vec<StatusCode>,
+
+    /// Retry when the request couldn't be sent, like when the connection is refused or times out
+    ///
+    /// Also covers [`RequestError::TransportError`] from custom transports.
+    pub retry_request_errors: bool,
+
+    /// Retry when the response body couldn't be read
+    pub retry_read_errors: bool,
+
+    /// Wait as long as the `Retry-After` header says instead of using the backoff
+    pub respect_retry_after: bool,
+
+    /// Longest wait a `Retry-After` header can ask for, longer waits are shortened to this
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Retry up to 3 attempts on server errors, rate limiting and connection problems
+    ///
+    /// Waits 200ms before the first retry, at most 10s between attempts, with jitter. A
+    /// `Retry-After` header can ask for at most 60s.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_request_errors: true,
+            retry_read_errors: true,
+            respect_retry_after: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::new()
+        }
+    }
+
+    /// Set how many times a request is sent at most, including the first attempt
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the wait before the first retry and the longest wait between two attempts
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Set if a random part of the wait should be removed
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set which status codes are retried
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Set if requests that couldn't be sent are retried
+    pub fn retry_request_errors(mut self, retry: bool) -> Self {
+        self.retry_request_errors = retry;
+        self
+    }
+
+    /// Set if responses that couldn't be read are retried
+    pub fn retry_read_errors(mut self, retry: bool) -> Self {
+        self.retry_read_errors = retry;
+        self
+    }
+
+    /// Set if the `Retry-After` header is used
+    pub fn respect_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    /// Set the longest wait a `Retry-After` header can ask for
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Check if a request that failed with `error` should be sent again
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{retry::RetryPolicy, types::error::RequestError};
+    /// let policy = RetryPolicy::new();
+    ///
+    /// assert!(policy.is_retryable(&RequestError::InternalServerError(String::new())));
+    /// assert!(!policy.is_retryable(&RequestError::Unauthorized(String::new())));
+    /// ```
+    pub fn is_retryable(&self, error: &RequestError) -> bool {
+        match error {
+            RequestError::RequestError(_) | RequestError::TransportError(_) => {
+                self.retry_request_errors
+            }
+            RequestError::ReadError(_) => self.retry_read_errors,
+            error => error
+                .status()
+                .is_some_and(|status| self.statuses.contains(&status)),
+        }
+    }
+
+    /// Get how long to wait after `attempt` failed, starting at 1
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use schoolsoft::retry::RetryPolicy;
+    /// let policy = RetryPolicy::new()
+    ///     .backoff(Duration::from_secs(1), Duration::from_secs(5))
+    ///     .jitter(false);
+    ///
+    /// assert_eq!(policy.backoff_for(1), Duration::from_secs(1));
+    /// assert_eq!(policy.backoff_for(2), Duration::from_secs(2));
+    /// assert_eq!(policy.backoff_for(3), Duration::from_secs(4));
+    /// assert_eq!(policy.backoff_for(4), Duration::from_secs(5));
+    /// ```
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+
+    /// Parse a `Retry-After` header value, either a number of seconds or a date
+    ///
+    /// Dates are compared with `now`, which should be in UTC, see
+    /// [`crate::clock::Clock::now_utc`].
+    ///
+    /// # Returns
+    /// `None` if the header should be ignored, otherwise the wait shortened to
+    /// [`Self::max_retry_after`]
+    pub(crate) fn retry_after(&self, value: &str, now: NaiveDateTime) -> Option<Duration> {
+        if !self.respect_retry_after {
+            return None;
+        }
+
+        let value = value.trim();
+
+        let wait = match value.parse() {
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => {
+                // Dates in the past mean that it is fine to retry right away
+                let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+                (date.naive_utc() - now).to_std().unwrap_or_default()
+            }
+        };
+
+        Some(wait.min(self.max_retry_after))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn jitter_within_range() {
+        let policy = RetryPolicy::new().backoff(Duration::from_secs(2), Duration::from_secs(10));
+
+        for _ in 0..100 {
+            let backoff = policy.backoff_for(1);
+            assert!(backoff >= Duration::from_secs(1));
+            assert!(backoff <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn retry_after() {
+        let policy = RetryPolicy::new();
+        let now = chrono::NaiveDate::from_ymd_opt(2015, 10, 21)
+            .unwrap()
+            .and_hms_opt(7, 27, 50)
+            .unwrap();
+
+        assert_eq!(policy.retry_after("3", now), Some(Duration::from_secs(3)));
+        assert_eq!(
+            policy.retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            policy.retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(policy.retry_after("soon", now), None);
+        assert_eq!(
+            policy.respect_retry_after(false).retry_after("3", now),
+            None
+        );
+    }
+
+    #[test]
+    fn max_retry_after() {
+        let policy = RetryPolicy::new().max_retry_after(Duration::from_secs(30));
+        let now = chrono::NaiveDate::from_ymd_opt(2015, 10, 21)
+            .unwrap()
+            .and_hms_opt(7, 28, 0)
+            .unwrap();
+
+        assert_eq!(
+            policy.retry_after("86400", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            policy.retry_after("Thu, 21 Oct 2100 07:28:00 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn statuses() {
+        let policy = RetryPolicy::new().statuses([StatusCode::BAD_GATEWAY]);
+
+        assert!(policy.is_retryable(&RequestError::UncheckedCode(
+            StatusCode::BAD_GATEWAY,
+            String::new()
+        )));
+        assert!(!policy.is_retryable(&RequestError::InternalServerError(String::new())));
+        assert!(!policy.is_retryable(&RequestError::NoOrganization));
+    }
+}
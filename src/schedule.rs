@@ -1,8 +1,13 @@
-use chrono::{Datelike, Local, Months, NaiveDate, NaiveTime, NaiveWeek, Weekday};
+use chrono::{Datelike, Months, NaiveDate, NaiveTime, NaiveWeek, Weekday};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{deserializers::Deserializer, types::error::ScheduleParseError, utils::WeekRange};
+use crate::{
+    clock::{Clock, SystemClock},
+    deserializers::Deserializer,
+    types::error::ScheduleParseError,
+    utils::WeekRange,
+};
 
 /// Holds the entire schedule
 #[derive(Debug)]
@@ -42,6 +47,12 @@ pub struct Lesson {
     pub end: chrono::NaiveTime,
     pub name: String,
     pub room: String,
+
+    /// Id of the [`crate::types::Org`] the lesson belongs to
+    pub org_id: u32,
+
+    /// Id of the [`Occasion`] the lesson comes from
+    pub occasion_id: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -53,7 +64,7 @@ pub struct RawOccasion {
     source: serde_json::Value,
     external_ref: String,
     subject_id: u64,
-    org_id: u64,
+    org_id: u32,
     upd_date: String,
     upd_by_type: i32,
     exclude_class: u64,
@@ -110,6 +121,9 @@ pub struct Occasion {
 
     /// The weeks when the lesson occurs
     pub weeks: Vec<u8>,
+
+    /// Id of the [`crate::types::Org`] the occasion belongs to
+    pub org_id: u32,
 }
 
 impl Schedule {
@@ -202,6 +216,166 @@ impl Schedule {
             }),
         }
     }
+
+    /// Deserialize a schedule, using `clock` to decide which year each week belongs to
+    ///
+    /// [`Deserializer::deserialize`] does the same thing using the [`SystemClock`].
+    pub fn deserialize_with_clock(data: &str, clock: &dyn Clock) -> Result<Self, ScheduleParseError> {
+        let raw: Vec<RawOccasion> =
+            serde_json::from_str(data).map_err(ScheduleParseError::SerdeError)?;
+
+        let mut schedule = Schedule::from(clock.today());
+
+        // Populate the schedule with lessons
+        for raw_occasion in raw {
+            let occasion = Occasion::try_from(raw_occasion)?;
+            let lesson = Lesson::from(&occasion);
+
+            // Add lesson to all the weeks it occurs in
+            for week in occasion.weeks {
+                let lessons = &mut schedule.weeks[(week - 1) as usize]
+                    .get_day(occasion.week_day)
+                    .lessons;
+
+                // Insert the lesson while keeping the list sorted
+                let pos = lessons.binary_search(&lesson).unwrap_or_else(|e| e);
+                lessons.insert(pos, lesson.clone());
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    /// Move all lessons from `other` into this schedule
+    ///
+    /// Used to combine the schedules of several [`crate::types::Org`]s into one, the lessons can
+    /// still be told apart using [`Lesson::org_id`]. Both schedules are expected to have been
+    /// created for the same school year, lessons in `other` on dates outside of this schedule
+    /// are dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chrono::{NaiveDate, NaiveTime};
+    /// # use schoolsoft::schedule::{Lesson, Schedule};
+    /// let today = NaiveDate::from_ymd_opt(2024, 4, 1).unwrap();
+    /// let lesson = |hour, org_id| Lesson {
+    ///     start: NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+    ///     end: NaiveTime::from_hms_opt(hour + 1, 0, 0).unwrap(),
+    ///     name: "Math".to_string(),
+    ///     room: "A1".to_string(),
+    ///     org_id,
+    ///     occasion_id: 1,
+    /// };
+    ///
+    /// let mut school = Schedule::from(today);
+    /// school.weeks[13].monday.lessons.push(lesson(10, 1));
+    ///
+    /// let mut leisure = Schedule::from(today);
+    /// leisure.weeks[13].monday.lessons.push(lesson(8, 2));
+    ///
+    /// school.merge(leisure);
+    ///
+    /// let orgs: Vec<u32> = school.weeks[13].monday.lessons.iter().map(|l| l.org_id).collect();
+    /// assert_eq!(orgs, vec![2, 1]);
+    /// ```
+    pub fn merge(&mut self, other: Schedule) {
+        for week in other.weeks {
+            for day in week.into_days() {
+                let Some(target) = self.day_mut(day.date) else {
+                    continue;
+                };
+
+                for lesson in day.lessons {
+                    // Insert the lesson while keeping the list sorted
+                    let pos = target.lessons.binary_search(&lesson).unwrap_or_else(|e| e);
+                    target.lessons.insert(pos, lesson);
+                }
+            }
+        }
+    }
+
+    /// Same as [`Self::day`] but mutable
+    fn day_mut(&mut self, date: NaiveDate) -> Option<&mut ScheduleDay> {
+        self.weeks
+            .iter_mut()
+            .find(|week| week.week.first_day() <= date && date <= week.week.last_day())
+            .map(|week| week.get_day(date.weekday()))
+    }
+
+    /// Get a specific day in the schedule
+    ///
+    /// # Returns
+    /// `None` if the date is outside of the school year the schedule covers
+    ///
+    /// # Examples
+    /// ```
+    /// # use chrono::NaiveDate;
+    /// # use schoolsoft::schedule::Schedule;
+    /// let schedule = Schedule::from(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 4, 3).unwrap();
+    /// assert_eq!(schedule.day(date).unwrap().date, date);
+    ///
+    /// assert!(schedule.day(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()).is_none());
+    /// ```
+    pub fn day(&self, date: NaiveDate) -> Option<&ScheduleDay> {
+        self.weeks
+            .iter()
+            .find(|week| week.week.first_day() <= date && date <= week.week.last_day())
+            .map(|week| week.day(date.weekday()))
+    }
+
+    /// Get the schedule for today according to `clock`
+    pub fn today(&self, clock: &dyn Clock) -> Option<&ScheduleDay> {
+        self.day(clock.today())
+    }
+
+    /// Find the next lesson that hasn't started yet according to `clock`
+    ///
+    /// # Returns
+    /// The day the lesson is on together with the lesson, or `None` if there are no more lessons
+    /// in the schedule.
+    ///
+    /// # Examples
+    /// ```
+    /// # use chrono::{NaiveDate, NaiveTime};
+    /// # use schoolsoft::clock::FixedClock;
+    /// # use schoolsoft::schedule::{Lesson, Schedule};
+    /// let today = NaiveDate::from_ymd_opt(2024, 4, 1).unwrap();
+    /// let clock = FixedClock::new(today.and_hms_opt(9, 0, 0).unwrap());
+    ///
+    /// let mut schedule = Schedule::from(today);
+    /// schedule.weeks[13].wednesday.lessons.push(Lesson {
+    ///     start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+    ///     end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+    ///     name: "Math".to_string(),
+    ///     room: "A1".to_string(),
+    ///     org_id: 1,
+    ///     occasion_id: 1,
+    /// });
+    ///
+    /// let (day, lesson) = schedule.next_lesson(&clock).unwrap();
+    /// assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 4, 3).unwrap());
+    /// assert_eq!(lesson.name, "Math");
+    /// ```
+    pub fn next_lesson(&self, clock: &dyn Clock) -> Option<(&ScheduleDay, &Lesson)> {
+        let now = clock.now();
+
+        for date in now.date().iter_days() {
+            let day = self.day(date)?;
+
+            // Lessons are sorted, so the first one that hasn't started is the next one
+            if let Some(lesson) = day
+                .lessons
+                .iter()
+                .find(|lesson| date.and_time(lesson.start) >= now)
+            {
+                return Some((day, lesson));
+            }
+        }
+
+        None
+    }
 }
 
 impl ScheduleDay {
@@ -273,35 +447,41 @@ impl ScheduleWeek {
             Weekday::Sun => &mut self.sunday,
         }
     }
+
+    /// Get a reference to a specific day in the week
+    ///
+    /// Same as [`Self::get_day`] without needing mutable access
+    pub fn day(&self, day: Weekday) -> &ScheduleDay {
+        match day {
+            Weekday::Mon => &self.monday,
+            Weekday::Tue => &self.tuesday,
+            Weekday::Wed => &self.wednesday,
+            Weekday::Thu => &self.thursday,
+            Weekday::Fri => &self.friday,
+            Weekday::Sat => &self.saturday,
+            Weekday::Sun => &self.sunday,
+        }
+    }
+
+    /// Take the days of the week, starting at monday
+    fn into_days(self) -> [ScheduleDay; 7] {
+        [
+            self.monday,
+            self.tuesday,
+            self.wednesday,
+            self.thursday,
+            self.friday,
+            self.saturday,
+            self.sunday,
+        ]
+    }
 }
 
 impl Deserializer for Schedule {
     type Error = ScheduleParseError;
 
     fn deserialize(data: &str) -> Result<Self, Self::Error> {
-        let raw: Vec<RawOccasion> =
-            serde_json::from_str(data).map_err(ScheduleParseError::SerdeError)?;
-
-        let mut schedule = Schedule::from(Local::now().naive_local().date());
-
-        // Populate the schedule with lessons
-        for raw_occasion in raw {
-            let occasion = Occasion::try_from(raw_occasion)?;
-            let lesson = Lesson::from(&occasion);
-
-            // Add lesson to all the weeks it occurs in
-            for week in occasion.weeks {
-                let lessons = &mut schedule.weeks[(week - 1) as usize]
-                    .get_day(occasion.week_day)
-                    .lessons;
-
-                // Insert the lesson while keeping the list sorted
-                let pos = lessons.binary_search(&lesson).unwrap_or_else(|e| e);
-                lessons.insert(pos, lesson.clone());
-            }
-        }
-
-        Ok(schedule)
+        Self::deserialize_with_clock(data, &SystemClock)
     }
 }
 
@@ -352,6 +532,7 @@ impl TryFrom<RawOccasion> for Occasion {
             room_name: value.room_name,
             week_day,
             weeks,
+            org_id: value.org_id,
         })
     }
 }
@@ -377,6 +558,8 @@ impl From<&Occasion> for Lesson {
             end: value.end_time,
             name: value.subject_name.clone(),
             room: value.room_name.clone(),
+            org_id: value.org_id,
+            occasion_id: value.id,
         }
     }
 }
@@ -424,6 +607,7 @@ mod day {
     use pretty_assertions::assert_eq;
 
     #[test]
+    #[allow(deprecated)]
     fn insert() {
         let mut day = ScheduleDay::new(NaiveDate::from_ymd(2024, 3, 25));
 
@@ -432,6 +616,8 @@ mod day {
             end: NaiveTime::from_hms(9, 0, 0),
             name: "Math".to_string(),
             room: "A1".to_string(),
+            org_id: 1,
+            occasion_id: 1,
         });
 
         day.lessons.push(Lesson {
@@ -439,6 +625,8 @@ mod day {
             end: NaiveTime::from_hms(10, 0, 0),
             name: "English".to_string(),
             room: "A2".to_string(),
+            org_id: 1,
+            occasion_id: 2,
         });
 
         let mut lessons = day.lessons.iter();
@@ -511,6 +699,7 @@ mod occasion {
         assert_eq!(occasion.end_time.to_string(), "09:30:00");
 
         assert_eq!(occasion.week_day, chrono::Weekday::Mon);
+        assert_eq!(occasion.org_id, 1);
         assert_eq!(
             occasion.weeks,
             vec![
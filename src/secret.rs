@@ -0,0 +1,138 @@
+//! Wrapper for values that shouldn't end up in logs
+//!
+//! Passwords, app keys and tokens are all stored as a [`Secret`]. They are hidden in [`Debug`] and
+//! [`Display`] output, so a [`crate::User`] can be printed safely, and the memory is overwritten
+//! when they are dropped.
+
+use std::fmt::{Debug, Display};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// What secrets are replaced with when printed
+pub const REDACTED: &str = "<redacted>";
+
+/// A string that is redacted when printed and zeroed when dropped
+///
+/// The value can only be read with [`Secret::expose`].
+///
+/// # Examples
+/// ```
+/// # use schoolsoft::secret::Secret;
+/// let password = Secret::from("hunter2");
+///
+/// assert_eq!(format!("{:?}", password), "<redacted>");
+/// assert_eq!(password.to_string(), "<redacted>");
+/// assert_eq!(password.expose(), "hunter2");
+/// ```
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap a value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Get the actual value
+    ///
+    /// Avoid keeping copies of it around longer than necessary.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<&Secret> for Secret {
+    fn from(value: &Secret) -> Self {
+        value.clone()
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Serialized as the plain value so it can be stored in a [`crate::session::Session`]
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Token, User, UserType};
+
+    #[test]
+    fn debug_hides_user_secrets() {
+        let user = User::new(
+            reqwest::Url::parse("https://sms.schoolsoft.se/mock_school").unwrap(),
+            "Mock User".to_string(),
+            "123notreal",
+            UserType::Student,
+            1337,
+            vec![],
+        );
+        user.set_token(Some(Token::new(
+            "one_of_those_tokens",
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+        )));
+
+        let debug = format!("{:?}", user);
+
+        assert!(debug.contains("Mock User"));
+        assert!(!debug.contains("123notreal"));
+        assert!(!debug.contains("one_of_those_tokens"));
+    }
+
+    #[test]
+    fn session_keeps_value() {
+        let secret = Secret::from("123notreal");
+        let json = serde_json::to_string(&secret).unwrap();
+
+        assert_eq!(json, r#""123notreal""#);
+        assert_eq!(serde_json::from_str::<Secret>(&json).unwrap(), secret);
+    }
+}
@@ -1,6 +1,22 @@
 use serde::ser::{Serialize, SerializeStruct};
 
-use crate::types::{error::SchoolListingError, LoginMethods, Lunch, LunchMenu, SchoolListing};
+use crate::types::{
+    error::SchoolListingError, LoginMethod, LoginMethods, Lunch, LunchMenu, SchoolListing,
+};
+#[cfg(feature = "unverified")]
+use crate::types::{
+    Absence, AbsenceScope, Attachment, Grade, NewsItem, PlanningItem, PlanningKind, Reporter,
+};
+
+/// Serialized as the number schoolsoft uses for the method
+impl Serialize for LoginMethod {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(u8::from(*self))
+    }
+}
 
 impl Serialize for LoginMethods {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -59,6 +75,144 @@ impl Serialize for LunchMenu {
     }
 }
 
+#[cfg(feature = "unverified")]
+impl Serialize for NewsItem {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("NewsItem", 8)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("title", &self.title)?;
+        state.serialize_field("body", &self.body)?;
+        state.serialize_field("author", &self.author)?;
+        state.serialize_field("published", &self.published)?;
+        state.serialize_field("expires", &self.expires)?;
+        state.serialize_field("read", &self.read)?;
+        state.serialize_field("attachments", &self.attachments)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl Serialize for Attachment {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Attachment", 4)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("size", &self.size)?;
+        state.serialize_field("content_type", &self.content_type)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl Serialize for PlanningItem {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("PlanningItem", 8)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("kind", &self.kind)?;
+        state.serialize_field("title", &self.title)?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("course", &self.course)?;
+        state.serialize_field("due", &self.due)?;
+        state.serialize_field("occasion_id", &self.occasion_id)?;
+        state.serialize_field("attachments", &self.attachments)?;
+        state.end()
+    }
+}
+
+/// Serialized as `"assignment"`, `"test"`, `"deadline"` or `"other"`
+#[cfg(feature = "unverified")]
+impl Serialize for PlanningKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            PlanningKind::Assignment => "assignment",
+            PlanningKind::Test => "test",
+            PlanningKind::Deadline => "deadline",
+            PlanningKind::Other => "other",
+        })
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl Serialize for Grade {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Grade", 6)?;
+        state.serialize_field("course_code", &self.course_code)?;
+        state.serialize_field("course_name", &self.course_name)?;
+        state.serialize_field("points", &self.points)?;
+        state.serialize_field("grade", &self.grade)?;
+        state.serialize_field("date", &self.date)?;
+        state.serialize_field("teacher", &self.teacher)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl Serialize for Absence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Absence", 7)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("date", &self.date)?;
+        state.serialize_field("scope", &self.scope)?;
+        state.serialize_field("minutes", &self.minutes)?;
+        state.serialize_field("reason", &self.reason)?;
+        state.serialize_field("valid", &self.valid)?;
+        state.serialize_field("reported_by", &self.reported_by)?;
+        state.end()
+    }
+}
+
+/// Serialized as `"whole_day"` or `{"lesson": occasion id}`
+#[cfg(feature = "unverified")]
+impl Serialize for AbsenceScope {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            AbsenceScope::Lesson(id) => {
+                serializer.serialize_newtype_variant("AbsenceScope", 0, "lesson", id)
+            }
+            AbsenceScope::WholeDay => {
+                serializer.serialize_unit_variant("AbsenceScope", 1, "whole_day")
+            }
+        }
+    }
+}
+
+/// Serialized as `"student"`, `"parent"`, `"staff"` or the number schoolsoft sent
+#[cfg(feature = "unverified")]
+impl Serialize for Reporter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Reporter::Student => serializer.serialize_str("student"),
+            Reporter::Parent => serializer.serialize_str("parent"),
+            Reporter::Staff => serializer.serialize_str("staff"),
+            Reporter::Other(other) => serializer.serialize_i32(*other),
+        }
+    }
+}
+
 impl Serialize for SchoolListingError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -90,14 +244,18 @@ impl Serialize for SchoolListingError {
 mod login_methods {
     use serde_json::json;
 
-    use crate::types::LoginMethods;
+    use crate::types::{LoginMethod, LoginMethods};
+
+    fn methods(raw: &[u8]) -> Vec<LoginMethod> {
+        raw.iter().copied().map(LoginMethod::from).collect()
+    }
 
     #[test]
     fn serialize() {
         let login_methods = LoginMethods {
-            student: vec![1, 2, 3],
-            teacher: vec![4, 5, 6],
-            parent: vec![7, 8, 9],
+            student: methods(&[1, 2, 3]),
+            teacher: methods(&[4, 5, 6]),
+            parent: methods(&[7, 8, 9]),
         };
 
         let expected = json!({
@@ -153,6 +311,10 @@ mod lunch {
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
+    #[cfg(feature = "unverified")]
+    use crate::types::{
+        Absence, AbsenceScope, Attachment, Grade, NewsItem, PlanningItem, PlanningKind, Reporter,
+    };
     use crate::types::{Lunch, LunchMenu};
 
     #[test]
@@ -230,6 +392,137 @@ mod lunch {
         let serialized = serde_json::to_value(lunch_menu).unwrap();
         assert_eq!(serialized, expected);
     }
+
+    #[cfg(feature = "unverified")]
+    #[test]
+    #[allow(deprecated)]
+    fn news_item() {
+        let news = NewsItem {
+            id: 1,
+            title: "Example title".to_string(),
+            body: "Example body".to_string(),
+            author: "Example author".to_string(),
+            published: chrono::NaiveDate::from_ymd(2021, 1, 1).and_hms(8, 0, 0),
+            expires: None,
+            read: false,
+            attachments: vec![Attachment {
+                id: 2,
+                name: "example.pdf".to_string(),
+                size: Some(1024),
+                content_type: None,
+            }],
+        };
+
+        let expected = json!({
+            "id": 1,
+            "title": "Example title",
+            "body": "Example body",
+            "author": "Example author",
+            "published": "2021-01-01T08:00:00",
+            "expires": null,
+            "read": false,
+            "attachments": [{
+                "id": 2,
+                "name": "example.pdf",
+                "size": 1024,
+                "content_type": null,
+            }],
+        });
+
+        let serialized = serde_json::to_value(news).unwrap();
+        assert_eq!(serialized, expected);
+    }
+
+    #[cfg(feature = "unverified")]
+    #[test]
+    #[allow(deprecated)]
+    fn planning_item() {
+        let planning = PlanningItem {
+            id: 1,
+            kind: PlanningKind::Assignment,
+            title: "Example title".to_string(),
+            description: "Example description".to_string(),
+            course: "Example course".to_string(),
+            due: chrono::NaiveDate::from_ymd(2021, 1, 1).and_hms(23, 59, 0),
+            occasion_id: Some(2),
+            attachments: Vec::new(),
+        };
+
+        let expected = json!({
+            "id": 1,
+            "kind": "assignment",
+            "title": "Example title",
+            "description": "Example description",
+            "course": "Example course",
+            "due": "2021-01-01T23:59:00",
+            "occasion_id": 2,
+            "attachments": [],
+        });
+
+        let serialized = serde_json::to_value(planning).unwrap();
+        assert_eq!(serialized, expected);
+    }
+
+    #[cfg(feature = "unverified")]
+    #[test]
+    #[allow(deprecated)]
+    fn grade() {
+        let grade = Grade {
+            course_code: "MATMAT01c".to_string(),
+            course_name: "Matematik 1c".to_string(),
+            points: Some(100),
+            grade: "A".to_string(),
+            date: Some(chrono::NaiveDate::from_ymd(2021, 1, 1)),
+            teacher: "Example teacher".to_string(),
+        };
+
+        let expected = json!({
+            "course_code": "MATMAT01c",
+            "course_name": "Matematik 1c",
+            "points": 100,
+            "grade": "A",
+            "date": "2021-01-01",
+            "teacher": "Example teacher",
+        });
+
+        let serialized = serde_json::to_value(grade).unwrap();
+        assert_eq!(serialized, expected);
+    }
+
+    #[cfg(feature = "unverified")]
+    #[test]
+    #[allow(deprecated)]
+    fn absence() {
+        let absence = |scope, reported_by| Absence {
+            id: 1,
+            date: chrono::NaiveDate::from_ymd(2021, 1, 1),
+            scope,
+            minutes: 60,
+            reason: None,
+            valid: true,
+            reported_by,
+        };
+
+        let serialized =
+            serde_json::to_value(absence(AbsenceScope::Lesson(2), Reporter::Parent)).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "id": 1,
+                "date": "2021-01-01",
+                "scope": { "lesson": 2 },
+                "minutes": 60,
+                "reason": null,
+                "valid": true,
+                "reported_by": "parent",
+            })
+        );
+
+        let serialized =
+            serde_json::to_value(absence(AbsenceScope::WholeDay, Reporter::Other(-1))).unwrap();
+        assert_eq!(serialized["scope"], json!("whole_day"));
+        assert_eq!(serialized["reported_by"], json!(-1));
+    }
 }
 
 #[cfg(test)]
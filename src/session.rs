@@ -0,0 +1,408 @@
+//! Persisting logged in users between runs
+//!
+//! Logging in is slow and schoolsoft starts throttling if it happens too often. A [`Session`]
+//! contains everything needed to rebuild a working [`User`] without the username and password,
+//! and a [`SessionStore`] decides where that session is kept.
+//!
+//! # Examples
+//!
+//! ```
+//! # use schoolsoft::ClientBuilder;
+//! # use schoolsoft::session::FileSessionStore;
+//! # async fn run() {
+//! let store = FileSessionStore::new("session.json");
+//! let mut client = ClientBuilder::new().build();
+//!
+//! // Only login if there was no saved session
+//! if !client.restore_session(&store).unwrap() {
+//!     client.login("username", "password", "school").await.unwrap();
+//!     client.user.as_ref().unwrap().save_session(&store).unwrap();
+//! }
+//! # }
+//! ```
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::secret::Secret;
+#[cfg(feature = "unverified")]
+use crate::types::Child;
+use crate::types::{error::SessionError, Org, Token, User, UserType};
+
+/// Everything needed to restore a logged in [`User`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Url of the school the user is logged in to
+    pub school_url: String,
+
+    /// Users full name
+    pub name: String,
+
+    /// Url to the users profile picture
+    pub picture_url: String,
+
+    /// If the user is over 18
+    pub is_of_age: bool,
+
+    /// Unique identifier for the user
+    pub user_id: u32,
+
+    /// What type of user this is
+    pub user_type: UserType,
+
+    /// List of organizations that the user is a part of
+    pub orgs: Vec<SessionOrg>,
+
+    /// The app key retrieved when logging in
+    pub app_key: Secret,
+
+    /// The last token that was retrieved, if any
+    pub token: Option<SessionToken>,
+
+    /// The selected child, only used by parents
+    #[cfg(feature = "unverified")]
+    #[serde(default)]
+    pub child: Option<SessionChild>,
+
+    /// Id of the selected org, if any
+    #[serde(default)]
+    pub org_id: Option<u32>,
+}
+
+/// Stored version of [`Org`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionOrg {
+    pub id: u32,
+    pub name: String,
+    pub blogger: bool,
+    pub school_type: u32,
+    pub leisure_school: u32,
+    pub class: String,
+    pub token_login: String,
+}
+
+/// Stored version of [`Child`]
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionChild {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub org_id: u32,
+}
+
+/// Stored version of [`Token`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionToken {
+    pub token: Secret,
+    pub expires: chrono::NaiveDateTime,
+}
+
+/// Somewhere to keep a [`Session`]
+///
+/// A store only ever holds one session, saving a new one replaces the old.
+pub trait SessionStore {
+    /// Load the stored session
+    ///
+    /// # Returns
+    /// `Ok(None)` if nothing has been saved yet
+    fn load(&self) -> Result<Option<Session>, SessionError>;
+
+    /// Save a session, replacing any previously saved one
+    fn save(&self, session: &Session) -> Result<(), SessionError>;
+
+    /// Remove the stored session
+    fn clear(&self) -> Result<(), SessionError>;
+}
+
+/// Keeps the session as json in a file
+///
+/// The file contains the app key, so on unix it is created readable by the owner only.
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    path: PathBuf,
+}
+
+/// Keeps the session in memory
+///
+/// Mostly useful for testing, or for handing a session between parts of a program.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    session: Mutex<Option<Session>>,
+}
+
+impl Session {
+    /// Rebuild the [`User`] this session was created from
+    ///
+    /// The user will reuse the stored token until [`User::smart_token`] decides that it needs to
+    /// be refreshed.
+    pub fn restore(self) -> Result<User, SessionError> {
+        let school_url =
+            Url::parse(&self.school_url).map_err(|_| SessionError::BadUrl(self.school_url.clone()))?;
+
+        let mut user = User::new(
+            school_url,
+            self.name,
+            self.app_key,
+            self.user_type,
+            self.user_id,
+            self.orgs.into_iter().map(Org::from).collect(),
+        );
+
+        user.pictute_url = self.picture_url;
+        user.is_of_age = self.is_of_age;
+        user.set_token(self.token.map(|token| Token::new(token.token, token.expires)));
+        #[cfg(feature = "unverified")]
+        {
+            user.child = self.child.map(Child::from);
+        }
+        user.org_id = self.org_id;
+
+        Ok(user)
+    }
+}
+
+impl From<&User> for Session {
+    fn from(user: &User) -> Self {
+        Session {
+            school_url: user.school_url.to_string(),
+            name: user.name.clone(),
+            picture_url: user.pictute_url.clone(),
+            is_of_age: user.is_of_age,
+            user_id: user.id,
+            user_type: user.user_type,
+            orgs: user.orgs.iter().cloned().map(SessionOrg::from).collect(),
+            app_key: user.app_key(),
+            token: user.token().map(|token| SessionToken {
+                token: token.token,
+                expires: token.expires,
+            }),
+            #[cfg(feature = "unverified")]
+            child: user.child.clone().map(SessionChild::from),
+            org_id: user.org_id,
+        }
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl From<Child> for SessionChild {
+    fn from(value: Child) -> Self {
+        SessionChild {
+            id: value.id,
+            name: value.name,
+            class: value.class,
+            org_id: value.org_id,
+        }
+    }
+}
+
+#[cfg(feature = "unverified")]
+impl From<SessionChild> for Child {
+    fn from(value: SessionChild) -> Self {
+        Child {
+            id: value.id,
+            name: value.name,
+            class: value.class,
+            org_id: value.org_id,
+        }
+    }
+}
+
+impl From<Org> for SessionOrg {
+    fn from(value: Org) -> Self {
+        SessionOrg {
+            id: value.id,
+            name: value.name,
+            blogger: value.blogger,
+            school_type: value.school_type,
+            leisure_school: value.leisure_school,
+            class: value.class,
+            token_login: value.token_login,
+        }
+    }
+}
+
+impl From<SessionOrg> for Org {
+    fn from(value: SessionOrg) -> Self {
+        Org {
+            id: value.id,
+            name: value.name,
+            blogger: value.blogger,
+            school_type: value.school_type,
+            leisure_school: value.leisure_school,
+            class: value.class,
+            token_login: value.token_login,
+        }
+    }
+}
+
+impl FileSessionStore {
+    /// Create a store that keeps the session at `path`
+    ///
+    /// The file is not touched until the first call to [`SessionStore::save`].
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Get the path of the session file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self) -> Result<Option<Session>, SessionError> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(SessionError::IoError(err)),
+        };
+
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(SessionError::SerdeError)
+    }
+
+    fn save(&self, session: &Session) -> Result<(), SessionError> {
+        let data = serde_json::to_vec_pretty(session).map_err(SessionError::SerdeError)?;
+
+        // Write to a temporary file first so a crash never leaves a half written session behind
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&temp).map_err(SessionError::IoError)?;
+        file.write_all(&data).map_err(SessionError::IoError)?;
+        file.sync_all().map_err(SessionError::IoError)?;
+
+        fs::rename(&temp, &self.path).map_err(SessionError::IoError)
+    }
+
+    fn clear(&self) -> Result<(), SessionError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(SessionError::IoError(err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl MemorySessionStore {
+    /// Create a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(&self) -> Result<Option<Session>, SessionError> {
+        Ok(self.session.lock().unwrap().clone())
+    }
+
+    fn save(&self, session: &Session) -> Result<(), SessionError> {
+        *self.session.lock().unwrap() = Some(session.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), SessionError> {
+        *self.session.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn session() -> Session {
+        Session {
+            school_url: "https://sms.schoolsoft.se/mock_school".to_string(),
+            name: "Mock User".to_string(),
+            picture_url: "pictureFile.jsp?studentId=1337".to_string(),
+            is_of_age: true,
+            user_id: 1337,
+            user_type: UserType::Student,
+            orgs: vec![SessionOrg {
+                id: 1,
+                name: "Mock School".to_string(),
+                blogger: false,
+                school_type: 9,
+                leisure_school: 0,
+                class: "F35b".to_string(),
+                token_login: "https://sms.schoolsoft.se/mock_school/jsp/app/TokenLogin.jsp?token=TOKEN_PLACEHOLDER".to_string(),
+            }],
+            app_key: Secret::from("123notreal"),
+            token: Some(SessionToken {
+                token: Secret::from("one_of_those_tokens"),
+                expires: chrono::NaiveDate::from_ymd_opt(2024, 2, 12)
+                    .unwrap()
+                    .and_hms_opt(17, 22, 23)
+                    .unwrap(),
+            }),
+            #[cfg(feature = "unverified")]
+            child: None,
+            org_id: None,
+        }
+    }
+
+    #[test]
+    fn roundtrip_user() {
+        let user = session().restore().expect("Restoring should work");
+
+        assert_eq!(user.name, "Mock User");
+        assert_eq!(user.app_key().expose(), "123notreal");
+        assert_eq!(user.user_type, UserType::Student);
+        assert_eq!(user.orgs[0].class, "F35b");
+        assert_eq!(user.token().unwrap().token.expose(), "one_of_those_tokens");
+
+        assert_eq!(Session::from(&user), session());
+    }
+
+    #[test]
+    fn bad_url() {
+        let mut session = session();
+        session.school_url = "not a url".to_string();
+
+        assert!(matches!(session.restore(), Err(SessionError::BadUrl(_))));
+    }
+
+    #[test]
+    fn memory_store() {
+        let store = MemorySessionStore::new();
+        assert_eq!(store.load().unwrap(), None);
+
+        store.save(&session()).unwrap();
+        assert_eq!(store.load().unwrap(), Some(session()));
+
+        store.clear().unwrap();
+        assert_eq!(store.load().unwrap(), None);
+    }
+
+    #[test]
+    fn file_store() {
+        let path = std::env::temp_dir().join(format!("schoolsoft-session-{}.json", std::process::id()));
+        let store = FileSessionStore::new(&path);
+        store.clear().unwrap();
+
+        assert_eq!(store.load().unwrap(), None);
+
+        store.save(&session()).unwrap();
+        assert_eq!(store.load().unwrap(), Some(session()));
+
+        store.clear().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,197 @@
+//! What actually sends requests over the network
+//!
+//! Everything the library sends to the app api ends up in a [`HttpTransport`]. The default is
+//! [`ReqwestTransport`], set your own with [`crate::ClientBuilder::transport`] to use another http
+//! client, answer requests in process during tests or record them.
+//!
+//! A transport only has to send a single request and read the response. Retrying, rate limiting,
+//! caching and middleware all happen before a request reaches it.
+//!
+//! [`crate::web::WebSession`] uses its own transport, since the website depends on cookies and
+//! redirects, see [`crate::web::WebSession::transport`]. Everything else is shared with the user
+//! that created it.
+
+use std::{fmt::Debug, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use reqwest::header::HeaderValue;
+use zeroize::Zeroizing;
+
+use crate::{
+    http::{is_secret_header, Request, Response},
+    types::error::RequestError,
+};
+
+/// A boxed future that can be sent between threads, returned by [`HttpTransport::send`]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Something that can send a [`Request`] and read the entire [`Response`]
+///
+/// The status code should not be checked, any response that was received is `Ok`. Failing to send
+/// the request or read the response should be reported as [`RequestError::TransportError`] by
+/// transports that don't use reqwest.
+///
+/// # Examples
+/// ```
+/// # use reqwest::StatusCode;
+/// # use schoolsoft::{
+/// #     http::{Request, Response},
+/// #     transport::{BoxFuture, HttpTransport},
+/// #     types::error::RequestError,
+/// #     ClientBuilder,
+/// # };
+/// /// Answers every request with an empty list
+/// #[derive(Debug)]
+/// struct Empty;
+///
+/// impl HttpTransport for Empty {
+///     fn send<'a>(&'a self, _request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+///         Box::pin(async {
+///             Ok(Response {
+///                 status: StatusCode::OK,
+///                 headers: Vec::new(),
+///                 body: "[]".to_string(),
+///             })
+///         })
+///     }
+/// }
+///
+/// let client = ClientBuilder::new()
+///     .transport(Empty)
+///     .build();
+/// ```
+pub trait HttpTransport: Debug + Send + Sync {
+    /// Send `request` once and read the response
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>>;
+}
+
+/// Settings for the [`reqwest::Client`]s created by the library
+///
+/// Set with the http methods on [`crate::ClientBuilder`], like
+/// [`crate::ClientBuilder::timeout`]. Used for the [`ReqwestTransport`] of the client and for
+/// every [`crate::web::WebSession`] its users create.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Longest time to wait for a connection to be established
+    pub connect_timeout: Option<Duration>,
+
+    /// Longest time a request can take, from connecting until the entire body is read
+    pub timeout: Option<Duration>,
+
+    /// Proxies used for requests, tried in order
+    pub proxies: Vec<reqwest::Proxy>,
+
+    /// Certificates trusted in addition to the system ones
+    pub root_certificates: Vec<reqwest::Certificate>,
+}
+
+impl HttpConfig {
+    /// Apply the settings to a [`reqwest::ClientBuilder`]
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.clone());
+        }
+
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        builder
+    }
+
+    /// Create a [`reqwest::Client`] with the settings
+    ///
+    /// # Errors
+    /// If reqwest can't create the client, like when a certificate is invalid.
+    pub fn client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        self.apply(reqwest::Client::builder()).build()
+    }
+}
+
+/// Transport that sends requests with a [`reqwest::Client`]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Create a transport that sends requests with `client`
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    /// Get the client used to send requests
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    async fn send_request(&self, request: &Request) -> Result<Response, RequestError> {
+        let mut builder = self
+            .client
+            .request(request.method.clone(), request.url.as_str());
+
+        for (name, value) in &request.headers {
+            if is_secret_header(name) {
+                let mut value = HeaderValue::from_maybe_shared(zeroized_on_drop(value))
+                    .map_err(|error| RequestError::TransportError(error.into()))?;
+                value.set_sensitive(true);
+                builder = builder.header(name.as_str(), value);
+            } else {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+        }
+
+        if let Some(body) = &request.body {
+            builder = builder.body(zeroized_on_drop(body.expose()));
+        }
+
+        let response = builder.send().await.map_err(RequestError::RequestError)?;
+
+        let status = response.status();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+
+        let body = response.text().await.map_err(RequestError::ReadError)?;
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Copy `value` for reqwest, which needs its own copy, in a buffer that is zeroed once reqwest
+/// drops it
+fn zeroized_on_drop(value: &str) -> Bytes {
+    Bytes::from_owner(Zeroizing::new(value.as_bytes().to_vec()))
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+        Box::pin(self.send_request(request))
+    }
+}
+
+/// Lets a transport be shared, like keeping a handle to it while the client uses it
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+        (**self).send(request)
+    }
+}
@@ -1,12 +1,14 @@
 //! Type definitions for the structs and errors that the wrapper can return
 
+use std::sync::Arc;
+
 use reqwest::Url;
-use serde_repr::Deserialize_repr;
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
 /// The type of user
 ///
 /// This enum represents the different types of users that can be logged in to schoolsoft.
-#[derive(Debug, Clone, Deserialize_repr, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, Eq)]
 #[repr(u8)]
 pub enum UserType {
     Student = 1,
@@ -21,15 +23,15 @@ pub enum UserType {
 ///
 /// While a appkey never changes, a token is only valid for 3 hours after which it must be
 /// refreshed using another call to /\[school\]/rest/app/token.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Token {
-    /// function for getting utc::now()
-    pub now: fn() -> chrono::NaiveDateTime,
+    /// Clock used to check if the token has expired
+    pub clock: Arc<dyn crate::clock::Clock>,
 
-    /// The token itself
-    pub token: String,
+    /// The token itself, hidden when printed
+    pub token: crate::secret::Secret,
 
-    /// When the token expires
+    /// When the token expires, in UTC, see [`crate::clock::Clock::now_utc`]
     pub expires: chrono::NaiveDateTime,
 }
 
@@ -64,8 +66,9 @@ pub struct Org {
     pub class: String,
 
     /// Url to login to the organization using a web browser
-    /// Once again, this field makes no since as you get it by logging in, so why would you need to
-    /// log in again?
+    ///
+    /// Contains [`crate::web::TOKEN_PLACEHOLDER`] which is replaced with a token to get logged in
+    /// to the website without a password, see [`User::web_session`].
     /// And no its not the url for getting the token, that is /\[school\]/rest/app/token
     pub token_login: String,
 }
@@ -74,10 +77,15 @@ pub struct Org {
 ///
 /// This struct represents a user of the schoolsoft system. It is deserialized from the JSON
 /// returned by the api when logging in.
+///
+/// The user is cheap to clone and can be shared between tasks. All clones share the same app key
+/// and token, so when one of them refreshes the token the rest will use the new one.
 #[derive(Debug, Clone)]
 pub struct User {
     pub school_url: Url,
-    pub client: reqwest::Client,
+
+    /// Sends all requests, shared with the [`crate::Client`] that created the user
+    pub(crate) http: crate::http::Http,
 
     /// Users full name
     pub name: String,
@@ -88,14 +96,10 @@ pub struct User {
     /// If the user is over 18 (schoolsoft is swedish)
     pub is_of_age: bool,
 
-    /// The app key retrieved when logging in
-    pub app_key: String,
-
-    /// Token used for interacting with api routes that require authentication
+    /// The app key and token, shared between clones of the user
     ///
-    /// This field is not populated by logging in. Instead it requires a separate request to
-    /// /\[school\]/rest/app/token with the app key.
-    pub token: Option<Token>,
+    /// See [`User::app_key`] and [`User::token`]
+    pub(crate) auth: Arc<crate::user::Auth>,
 
     /// What type of user this is
     pub user_type: UserType,
@@ -105,19 +109,72 @@ pub struct User {
 
     /// List of organizations that the user is a part of
     pub orgs: Vec<Org>,
+
+    /// Used to login again if schoolsoft stops accepting the app key
+    ///
+    /// See [`crate::credentials`]
+    pub credentials: Option<Arc<dyn crate::credentials::CredentialProvider>>,
+
+    /// Clock given to every token the user retrieves, see [`User::with_clock`]
+    pub clock: Arc<dyn crate::clock::Clock>,
+
+    /// Device identity sent with every request, see [`crate::device`]
+    pub device: crate::device::DeviceProfile,
+
+    /// The child requests are made for, only used by parents
+    ///
+    /// See [`User::get_children`] and [`User::select_child`]
+    #[cfg(feature = "unverified")]
+    pub child: Option<Child>,
+
+    /// Id of the [`Org`] requests are made for
+    ///
+    /// See [`User::org`] for what is used when this isn't set, and [`User::select_org`]
+    pub org_id: Option<u32>,
 }
 
-/// The login methods available for a school
+/// A child of a parent user
 ///
-/// Represented as an array of numbers. If the number for a login method is present in the array,
-/// its available.
+/// Parents don't have a schedule or lunch menu of their own. Instead one of their children has to
+/// be selected with [`User::select_child`] and requests are then made on behalf of that child.
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Child {
+    /// Unique identifier for the child, the same as the childs own user id
+    pub id: u32,
+
+    /// Childs full name
+    pub name: String,
+
+    /// The class the child is attending
+    pub class: String,
+
+    /// Id of the [`Org`] the child belongs to
+    pub org_id: u32,
+}
+
+/// A way of logging in to schoolsoft
 ///
-/// For the app api to work 4 must be present.
+/// Schoolsoft represents login methods as numbers, only the one used by the app api has a known
+/// meaning. The rest are kept as [`LoginMethod::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoginMethod {
+    /// Logging in with a username and password through the app api, required by this library
+    App,
+
+    /// Any other login method
+    Other(u8),
+}
+
+/// The login methods available for a school
+///
+/// Separate for each [`UserType`]. For the app api to work [`LoginMethod::App`] must be present,
+/// see [`LoginMethods::supports_app`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LoginMethods {
-    pub student: Vec<u8>,
-    pub teacher: Vec<u8>,
-    pub parent: Vec<u8>,
+    pub student: Vec<LoginMethod>,
+    pub teacher: Vec<LoginMethod>,
+    pub parent: Vec<LoginMethod>,
 }
 
 /// A schoolsoft school
@@ -149,6 +206,163 @@ pub struct Lunch {
     pub food: String,
 }
 
+/// A news item posted by the school
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewsItem {
+    pub id: u64,
+    pub title: String,
+
+    /// The text of the news item, may contain html
+    pub body: String,
+
+    /// Name of whoever posted it
+    pub author: String,
+
+    /// When the news item was published
+    pub published: chrono::NaiveDateTime,
+
+    /// When the news item stops being shown, None if it never does
+    pub expires: Option<chrono::NaiveDateTime>,
+
+    /// If the user has opened the news item
+    pub read: bool,
+
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to a [`NewsItem`] or [`PlanningItem`]
+///
+/// Only describes the file, the content has to be downloaded through the website, see
+/// [`crate::web::WebSession`].
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub id: u64,
+
+    /// File name, including the extension
+    pub name: String,
+
+    /// Size in bytes, if schoolsoft sent it
+    pub size: Option<u64>,
+
+    /// Mime type, if schoolsoft sent it
+    pub content_type: Option<String>,
+}
+
+
+/// An assignment, test or deadline planned for a course
+///
+/// Use [`PlanningItem::lesson`] to find the lesson it belongs to in a
+/// [`crate::schedule::Schedule`].
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanningItem {
+    pub id: u64,
+    pub kind: PlanningKind,
+    pub title: String,
+
+    /// Instructions for the student, may contain html
+    pub description: String,
+
+    /// Name of the course the item is for
+    pub course: String,
+
+    /// When the item is due, like when an assignment has to be handed in or when a test starts
+    pub due: chrono::NaiveDateTime,
+
+    /// Id of the [`crate::schedule::Occasion`] of the lesson the item belongs to, if any
+    pub occasion_id: Option<u64>,
+
+    pub attachments: Vec<Attachment>,
+}
+
+/// What kind of [`PlanningItem`] something is
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanningKind {
+    Assignment,
+    Test,
+    Deadline,
+
+    /// A kind schoolsoft hasn't been seen using
+    Other,
+}
+
+/// A grade set for a course
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grade {
+    /// Code of the course, like `MATMAT01c`
+    pub course_code: String,
+
+    /// Name of the course
+    pub course_name: String,
+
+    /// How many points the course is worth, if it has points
+    pub points: Option<u32>,
+
+    /// The grade as schoolsoft shows it, like `A` or `F`
+    pub grade: String,
+
+    /// When the grade was set, if schoolsoft knows
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Name of the teacher that set the grade
+    pub teacher: String,
+}
+
+/// A registered absence for a student
+///
+/// Use [`Absence::lesson`] to find the lesson in a [`crate::schedule::Schedule`].
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Absence {
+    pub id: u64,
+
+    /// The day the student was absent
+    pub date: chrono::NaiveDate,
+
+    /// If a single lesson or the whole day was missed
+    pub scope: AbsenceScope,
+
+    /// How long the student was absent for
+    pub minutes: u32,
+
+    /// Reason given when the absence was reported, if any
+    pub reason: Option<String>,
+
+    /// If the absence is valid, like when the student was sick, or not
+    pub valid: bool,
+
+    /// Who reported the absence
+    pub reported_by: Reporter,
+}
+
+/// What an [`Absence`] covers
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbsenceScope {
+    /// A single lesson, with the id of the [`crate::schedule::Occasion`] it belongs to
+    Lesson(u64),
+
+    /// Every lesson during the day
+    WholeDay,
+}
+
+/// Who reported an [`Absence`]
+#[cfg(feature = "unverified")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reporter {
+    Student,
+    Parent,
+
+    /// A teacher or other school staff
+    Staff,
+
+    /// A user type schoolsoft hasn't been seen using, kept as the number it sent
+    Other(i32),
+}
 
 /// Types used when something goes wrong
 pub mod error {
@@ -164,14 +378,125 @@ pub mod error {
         #[error("Error when reading the response: {0}")]
         ReadError(reqwest::Error),
 
+        /// Includes the start of the response body, see [`RequestError::body`]
         #[error("Unauthorized")]
-        Unauthorized,
+        Unauthorized(String),
 
+        /// Includes the start of the response body, see [`RequestError::body`]
         #[error("Internal server error")]
-        InternalServerError,
+        InternalServerError(String),
 
+        /// Includes the start of the response body, see [`RequestError::body`]
         #[error("Response returned a unexpected status code: {0}")]
-        UncheckedCode(reqwest::StatusCode),
+        UncheckedCode(reqwest::StatusCode, String),
+
+        #[error("A child has to be selected before making requests as a parent")]
+        NoChildSelected,
+
+        /// Children can only be selected with the `unverified` feature, so requests made as a
+        /// parent fail without it instead of being sent without a child
+        #[error("Requests made as a parent need the unverified feature")]
+        ParentsUnsupported,
+
+        #[error("The user is not part of any organization")]
+        NoOrganization,
+
+        #[error("The user is not part of an organization with id {0}")]
+        UnknownOrganization(u32),
+
+        #[error("Rate limit reached, there is room for another request in {0:?}")]
+        RateLimited(std::time::Duration),
+
+        /// Sending the request or reading the response failed in a custom
+        /// [`crate::transport::HttpTransport`]
+        #[error("Error when sending request: {0}")]
+        TransportError(Box<dyn std::error::Error + Send + Sync>),
+    }
+
+    /// Any error returned by the library
+    ///
+    /// Every method returns its own error type, like [`LunchMenuError`], that only has the
+    /// variants that can happen there. All of them convert into this with `?` or `into()`, so one
+    /// policy can handle errors from every endpoint through [`Error::kind`], [`Error::status`] and
+    /// friends. The message only says what failed, the specific error is kept as
+    /// [`std::error::Error::source`].
+    #[derive(Error, Debug)]
+    pub enum Error {
+        #[error("Sending a request failed")]
+        Request(#[from] RequestError),
+
+        #[error("Logging in failed")]
+        Login(#[from] LoginError),
+
+        #[error("Getting a token failed")]
+        Token(#[from] TokenError),
+
+        #[error("Getting the school list failed")]
+        SchoolListing(#[from] SchoolListingError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Getting the children failed")]
+        Children(#[from] ChildrenError),
+
+        #[error("Starting a web session failed")]
+        WebSession(#[from] WebSessionError),
+
+        #[error("Saving or restoring the session failed")]
+        Session(#[from] SessionError),
+
+        #[error("Getting the lunch menu failed")]
+        LunchMenu(#[from] LunchMenuError),
+
+        #[error("Getting the schedule failed")]
+        Schedule(#[from] ScheduleError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Getting the news failed")]
+        News(#[from] NewsError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Getting the absences failed")]
+        Absence(#[from] AbsenceError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Reporting the absence failed")]
+        Report(#[from] ReportError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Getting the grades failed")]
+        Grades(#[from] GradesError),
+
+        #[cfg(feature = "unverified")]
+        #[error("Getting the planning failed")]
+        Planning(#[from] PlanningError),
+    }
+
+    /// What went wrong, independent of which endpoint it happened at
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// The request couldn't be sent or the response couldn't be read
+        Connection,
+
+        /// Schoolsoft rejected the password, app key or token
+        Auth,
+
+        /// The rate limiter or schoolsoft refused to send more requests for now
+        RateLimited,
+
+        /// Schoolsoft answered with a server error
+        Server,
+
+        /// Schoolsoft answered with any other unexpected status code
+        Status,
+
+        /// The response couldn't be parsed
+        Parse,
+
+        /// The request doesn't make sense for the user, like getting the children of a student
+        InvalidRequest,
+
+        /// A session couldn't be saved or restored
+        Session,
     }
 
     /// Error that can happen when trying to get a list of schools.
@@ -195,6 +520,9 @@ pub mod error {
 
         #[error("Error when reading the response: {0}")]
         ParseError(serde_json::Error),
+
+        #[error("Error when logging in again after the app key was rejected: {0}")]
+        LoginError(LoginError),
     }
 
     /// Error that can happen when trying to login.
@@ -205,6 +533,59 @@ pub mod error {
 
         #[error("Error when reading the response: {0}")]
         ParseError(serde_json::Error),
+
+        #[error("The school doesn't allow {0} accounts to login through the app")]
+        AppLoginUnsupported(super::UserType),
+    }
+
+    /// Error that can happen when trying to get the children of a parent.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum ChildrenError {
+        #[error("Only parents have children")]
+        NotAParent,
+
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(serde_json::Error),
+    }
+
+    /// Error that can happen when starting a web session.
+    #[derive(Error, Debug)]
+    pub enum WebSessionError {
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+    }
+
+    /// Error that can happen when saving or restoring a session.
+    #[derive(Error, Debug)]
+    pub enum SessionError {
+        #[error("Error when accessing the session store: {0}")]
+        IoError(std::io::Error),
+
+        #[error("Error when reading or writing the session: {0}")]
+        SerdeError(serde_json::Error),
+
+        #[error("The stored school url is invalid: {0}")]
+        BadUrl(String),
+    }
+
+    /// Error that can happen when loading or saving a cassette.
+    #[derive(Error, Debug)]
+    pub enum CassetteError {
+        #[error("Error when accessing the cassette file: {0}")]
+        IoError(std::io::Error),
+
+        #[error("Error when reading or writing the cassette: {0}")]
+        SerdeError(serde_json::Error),
     }
 
     /// Error that can happen when trying to get a lunch menu.
@@ -220,6 +601,106 @@ pub mod error {
         ParseError(LunchMenuParseError),
     }
 
+    /// Error that can happen when trying to get absences.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum AbsenceError {
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(AbsenceParseError),
+    }
+
+    /// Error that can happen when trying to report absence.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum ReportError {
+        #[error("Invalid absence report: {0}")]
+        InvalidReport(ReportValidationError),
+
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(serde_json::Error),
+    }
+
+    /// Reason an absence report was refused before being sent, see
+    /// [`crate::report::AbsenceReport::validate`]
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ReportValidationError {
+        #[error("Only parents and students that are of age can report absence")]
+        NotAllowed,
+
+        #[error("The report starts on {0} which is after it ends on {1}")]
+        InvalidRange(chrono::NaiveDate, chrono::NaiveDate),
+
+        #[error("No lessons were selected")]
+        NoLessonsSelected,
+
+        #[error("Absence can't be reported for {0} since it has already passed")]
+        InPast(chrono::NaiveDate),
+
+        #[error("{0} is not part of the schedule")]
+        OutsideSchedule(chrono::NaiveDate),
+
+        #[error("There is no lesson with occasion id {1} on {0}")]
+        UnknownLesson(chrono::NaiveDate, u64),
+
+        #[error("There are no lessons between {0} and {1}")]
+        NoLessons(chrono::NaiveDate, chrono::NaiveDate),
+    }
+
+    /// Error that can happen when trying to get grades.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum GradesError {
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(GradesParseError),
+    }
+
+    /// Error that can happen when trying to get planning.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum PlanningError {
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(PlanningParseError),
+    }
+
+    /// Error that can happen when trying to get news.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum NewsError {
+        #[error("Error when sending request: {0}")]
+        RequestError(RequestError),
+
+        #[error("Error when retrieving new token: {0}")]
+        TokenError(TokenError),
+
+        #[error("Error when reading the response: {0}")]
+        ParseError(NewsParseError),
+    }
+
     #[derive(Error, Debug)]
     pub enum ScheduleError {
         #[error("Error when sending request: {0}")]
@@ -244,6 +725,50 @@ pub mod error {
         #[error("Error when parsing date: {0}")]
         DateParseError(String, chrono::ParseError),
     }
+
+    /// Error that can happen when trying to parse absences.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum AbsenceParseError {
+        #[error("Error when parsing json: {0}")]
+        SerdeError(serde_json::Error),
+
+        #[error("Error when parsing date: {0}")]
+        DateParseError(String, chrono::ParseError),
+    }
+
+    /// Error that can happen when trying to parse grades.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum GradesParseError {
+        #[error("Error when parsing json: {0}")]
+        SerdeError(serde_json::Error),
+
+        #[error("Error when parsing date: {0}")]
+        DateParseError(String, chrono::ParseError),
+    }
+
+    /// Error that can happen when trying to parse planning.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum PlanningParseError {
+        #[error("Error when parsing json: {0}")]
+        SerdeError(serde_json::Error),
+
+        #[error("Error when parsing date: {0}")]
+        DateParseError(String, chrono::ParseError),
+    }
+
+    /// Error that can happen when trying to parse news.
+    #[cfg(feature = "unverified")]
+    #[derive(Error, Debug)]
+    pub enum NewsParseError {
+        #[error("Error when parsing json: {0}")]
+        SerdeError(serde_json::Error),
+
+        #[error("Error when parsing date: {0}")]
+        DateParseError(String, chrono::ParseError),
+    }
     
     #[derive(Error, Debug)]
     pub enum ScheduleParseError {
@@ -1,58 +1,360 @@
+use std::sync::{Arc, Mutex};
+
+use crate::cache::Endpoint;
+use crate::clock::{Clock, SystemClock};
+use crate::credentials::CredentialProvider;
 use crate::deserializers::Deserializer;
+use crate::device::DeviceProfile;
 use crate::rest;
 use crate::schedule::Schedule;
-use crate::types::error::{LunchMenuError, ScheduleError, TokenError};
+use crate::secret::Secret;
+use crate::session::{Session, SessionStore};
+use crate::types::error::{
+    LoginError, LunchMenuError, RequestError, ScheduleError, SessionError, TokenError,
+};
 use crate::types::LunchMenu;
-use crate::utils::{api, make_request};
+#[cfg(feature = "unverified")]
+use crate::{
+    report::{AbsenceReport, ReportOutcome},
+    types::{
+        error::{AbsenceError, ChildrenError, GradesError, NewsError, PlanningError, ReportError},
+        Absence, Child, Grade, NewsItem, PlanningItem,
+    },
+};
+use crate::http::{Http, Request};
+use crate::instrument;
+use crate::utils::{api_for, school_name};
 use chrono::Duration;
 use reqwest::Url;
+use serde::de::Error as _;
 
 pub use crate::types::{Org, Token, User, UserType};
 
+/// The parts of a [`User`] that change while it is being used
+///
+/// Shared between all clones of a user so that a token refreshed by one of them is used by all.
+#[derive(Debug)]
+pub(crate) struct Auth {
+    app_key: Mutex<Secret>,
+    token: Mutex<Option<Token>>,
+
+    /// Held while a new token is being retrieved so only one request is made at a time
+    refresh: tokio::sync::Mutex<()>,
+}
+
+impl Auth {
+    pub(crate) fn new(app_key: Secret) -> Self {
+        Self {
+            app_key: Mutex::new(app_key),
+            token: Mutex::new(None),
+            refresh: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
 impl User {
     /// Manually create a new user
+    ///
+    /// The user gets default http settings. Pass it to [`crate::Client::adopt`] to use the
+    /// transport, timeouts, proxies and everything else configured on a client instead.
     pub fn new(
         school_url: Url,
         name: String,
-        app_key: String,
+        app_key: impl Into<Secret>,
         user_type: UserType,
         id: u32,
         orgs: Vec<Org>,
     ) -> Self {
         Self {
             school_url,
-            client: reqwest::Client::new(),
+            http: Http::default(),
             name,
             pictute_url: String::new(),
             is_of_age: false,
-            app_key,
-            token: None,
+            auth: Arc::new(Auth::new(app_key.into())),
             user_type,
             id,
             orgs,
+            credentials: None,
+            clock: Arc::new(SystemClock),
+            device: DeviceProfile::default(),
+            #[cfg(feature = "unverified")]
+            child: None,
+            org_id: None,
+        }
+    }
+
+    /// Login to a school
+    ///
+    /// `school_url` is the full url of the school, e.g `https://sms.schoolsoft.se/school`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "login",
+            skip_all,
+            fields(school = crate::instrument::redact_url(school_url), user_type = ?user_type)
+        )
+    )]
+    pub(crate) async fn login(
+        http: &Http,
+        device: &DeviceProfile,
+        school_url: &str,
+        username: &str,
+        password: &Secret,
+        user_type: UserType,
+    ) -> Result<User, LoginError> {
+        let url = rest!(school_url, login);
+
+        // Construct body, the password is only exposed while it is encoded into the body, which
+        // keeps it as a secret
+        let user_type = (user_type as u8).to_string();
+        let params = [
+            ("identification", username),
+            ("verification", password.expose()),
+            ("logintype", "4"),
+            ("usertype", &user_type),
+        ];
+
+        let mut request = device.apply(Request::post(url).form(&params));
+        if let Some(school) = school_name(school_url) {
+            request = request.school(school);
+        }
+
+        let data = http
+            .text(request)
+            .await
+            .map_err(LoginError::RequestError)?;
+
+        // Parse response, the url the login was sent to is only used as the school url for users
+        // without any orgs
+        let mut user = instrument::parse("login", || {
+            let school_url = Url::parse(school_url).map_err(serde_json::Error::custom)?;
+            User::deserialize_with_url(&data, school_url)
+        })
+        .map_err(LoginError::ParseError)?;
+        user.http = http.clone();
+        user.device = device.clone();
+
+        Ok(user)
+    }
+
+    /// Get the app key retrieved when logging in
+    pub fn app_key(&self) -> Secret {
+        self.auth.app_key.lock().unwrap().clone()
+    }
+
+    /// Replace the app key
+    ///
+    /// Affects all clones of the user. The current token is kept.
+    pub fn set_app_key(&self, app_key: impl Into<Secret>) {
+        *self.auth.app_key.lock().unwrap() = app_key.into();
+    }
+
+    /// Get the current token, if any
+    ///
+    /// A token is not retrieved by logging in. Instead it requires a separate request to
+    /// /\[school\]/rest/app/token with the app key, see [`Self::smart_token`].
+    pub fn token(&self) -> Option<Token> {
+        self.auth.token.lock().unwrap().clone()
+    }
+
+    /// Replace the current token
+    ///
+    /// Affects all clones of the user.
+    pub fn set_token(&self, token: Option<Token>) {
+        *self.auth.token.lock().unwrap() = token;
+    }
+
+    /// Attach a [`CredentialProvider`] used to login again when the app key stops working
+    ///
+    /// See [`Self::smart_token`]
+    pub fn with_credentials(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+
+    /// Use a custom [`Clock`] for checking if tokens have expired and for placing the schedule
+    /// in the right year
+    ///
+    /// The current token, if any, is switched over to the new clock as well.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        if let Some(mut token) = self.token() {
+            token.clock = clock.clone();
+            self.set_token(Some(token));
+        }
+
+        self.clock = clock;
+        self
+    }
+
+    /// Login again using the attached [`CredentialProvider`] and replace the app key
+    ///
+    /// The current token is thrown away as it was issued for the old app key.
+    ///
+    /// # Returns
+    /// `Ok(false)` without making any requests if there is no [`CredentialProvider`] attached.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(school = %self.school_url, user = self.id))
+    )]
+    pub async fn reauthenticate(&self) -> Result<bool, LoginError> {
+        let Some(provider) = &self.credentials else {
+            return Ok(false);
+        };
+
+        let credentials = provider.credentials();
+        let fresh = User::login(
+            &self.http,
+            &self.device,
+            self.school_url.as_str(),
+            &credentials.username,
+            &credentials.password,
+            self.user_type,
+        )
+        .await?;
+
+        self.set_app_key(fresh.app_key());
+        self.set_token(None);
+
+        Ok(true)
+    }
+
+    /// Get the children of a parent
+    ///
+    /// # Errors
+    /// [`ChildrenError::NotAParent`] without making any requests if the user isn't a parent
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "children", skip_all, fields(school = %self.school_url, user = self.id))
+    )]
+    pub async fn get_children(&self) -> Result<Vec<Child>, ChildrenError> {
+        if self.user_type != UserType::Parent {
+            return Err(ChildrenError::NotAParent);
+        }
+
+        let org = self
+            .org()
+            .ok_or(ChildrenError::RequestError(RequestError::NoOrganization))?;
+
+        let url = format!(
+            "{}/api/students/{}/{}",
+            self.school_url, self.user_type, org.id
+        );
+
+        let request = self
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Children);
+
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self
+                    .smart_token()
+                    .await
+                    .map_err(ChildrenError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(ChildrenError::RequestError)?
+            }
+        };
+
+        instrument::parse("children", || Child::deserialize_many(&response))
+            .map_err(ChildrenError::ParseError)
+    }
+
+    /// Make all following requests on behalf of `child`
+    ///
+    /// Required for parents before getting anything child specific like the schedule or lunch
+    /// menu, see [`Self::get_children`].
+    #[cfg(feature = "unverified")]
+    pub fn select_child(&mut self, child: Child) {
+        self.child = Some(child);
+    }
+
+    /// Same as [`Self::select_child`] but takes and returns the user
+    #[cfg(feature = "unverified")]
+    pub fn with_child(mut self, child: Child) -> Self {
+        self.select_child(child);
+        self
+    }
+
+    /// Get the org requests are made for
+    ///
+    /// This is the org selected with [`Self::select_org`]. If none has been selected it is the org
+    /// of the selected child for parents with the `unverified` feature, otherwise the first org the
+    /// user is a part of.
+    ///
+    /// # Returns
+    /// `None` if the user isn't part of any org
+    pub fn org(&self) -> Option<&Org> {
+        #[cfg(feature = "unverified")]
+        let id = self
+            .org_id
+            .or_else(|| self.child.as_ref().map(|child| child.org_id));
+
+        #[cfg(not(feature = "unverified"))]
+        let id = self.org_id;
+
+        match id {
+            Some(id) => self.orgs.iter().find(|org| org.id == id),
+            None => self.orgs.first(),
+        }
+    }
+
+    /// Make all following requests for the org with the id `org_id`
+    ///
+    /// # Errors
+    /// [`RequestError::UnknownOrganization`] if the user isn't part of the org, the active org is
+    /// left unchanged.
+    pub fn select_org(&mut self, org_id: u32) -> Result<(), RequestError> {
+        if !self.orgs.iter().any(|org| org.id == org_id) {
+            return Err(RequestError::UnknownOrganization(org_id));
         }
+
+        self.org_id = Some(org_id);
+        Ok(())
+    }
+
+    /// Save the user to a [`SessionStore`]
+    ///
+    /// The saved session can later be restored with [`crate::Client::restore_session`] or
+    /// [`Session::restore`] without having to login again.
+    ///
+    /// The current token is saved as well, so it is worth calling this again after the token has
+    /// been refreshed.
+    pub fn save_session(&self, store: &impl SessionStore) -> Result<(), SessionError> {
+        store.save(&Session::from(self))
     }
 
     /// Get a new token for the user
     ///
     /// This method uses the app key to get a new token from the schoolsoft api. The token is then
     /// used to authenticate to the api when making other requests.
-    pub async fn get_token(&mut self) -> Result<Token, TokenError> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "token", skip_all, fields(school = %self.school_url, user = self.id))
+    )]
+    pub async fn get_token(&self) -> Result<Token, TokenError> {
         let url = rest!(self.school_url, token);
 
         let request = self
-            .client
-            .post(url)
-            .header("appKey", &self.app_key)
-            .header("deviceid", "TempleOs");
+            .request(reqwest::Method::POST, url)
+            .header("appKey", self.app_key().expose());
 
-        let response = make_request(request)
+        let response = self
+            .http
+            .text(request)
             .await
             .map_err(TokenError::RequestError)?;
 
-        let token = Token::deserialize(&response).map_err(TokenError::ParseError)?;
+        let mut token = instrument::parse("token", || Token::deserialize(&response))
+            .map_err(TokenError::ParseError)?;
+        token.clock = self.clock.clone();
 
-        self.token = Some(token.clone());
+        self.set_token(Some(token.clone()));
 
         Ok(token)
     }
@@ -62,69 +364,520 @@ impl User {
     /// Checks if the current token is close to expiring.
     /// If it is it will get a new token and replace [`Self::token`] with the new one.
     ///
+    /// If several clones of the user need a new token at the same time, only one request is made
+    /// and the rest wait for its result.
+    ///
+    /// If schoolsoft rejects the app key and a [`CredentialProvider`] is attached, the user logs
+    /// in again with [`Self::reauthenticate`] and retries getting the token once.
+    ///
     /// # Returns
     /// The [`Token`] or an error if a new token had to be fetched and failed.
-    pub async fn smart_token(&mut self) -> Result<Token, TokenError> {
-        match &self.token {
-            Some(token) if token.is_safe() => Ok(token.clone()),
-            _ => self.get_token().await,
+    pub async fn smart_token(&self) -> Result<Token, TokenError> {
+        if let Some(token) = self.safe_token() {
+            return Ok(token);
+        }
+
+        let _refreshing = self.auth.refresh.lock().await;
+
+        // Someone else might have refreshed the token while we were waiting
+        if let Some(token) = self.safe_token() {
+            return Ok(token);
+        }
+
+        match self.get_token().await {
+            Err(TokenError::RequestError(RequestError::Unauthorized(body))) => {
+                if !self
+                    .reauthenticate()
+                    .await
+                    .map_err(TokenError::LoginError)?
+                {
+                    return Err(TokenError::RequestError(RequestError::Unauthorized(body)));
+                }
+
+                self.get_token().await
+            }
+            result => result,
         }
     }
 
-    /// Get this weeks lunch menu
+    /// Remove every cached response belonging to this user, see [`crate::cache::Cache`]
+    ///
+    /// Does nothing if the client doesn't have a cache.
+    pub fn invalidate_cache(&self) {
+        if let Some(cache) = self.http.cache() {
+            cache.invalidate_user(self.id);
+        }
+    }
+
+    /// Start a request with the device headers added, see [`DeviceProfile`]
+    pub(crate) fn request(&self, method: reqwest::Method, url: impl Into<String>) -> Request {
+        let request = self.device.apply(Request::new(method, url)).user(self.id);
+
+        match school_name(self.school_url.as_str()) {
+            Some(school) => request.school(school),
+            None => request,
+        }
+    }
+
+    /// Get the current token if it is safe to use
+    fn safe_token(&self) -> Option<Token> {
+        self.token().filter(Token::is_safe)
+    }
+
+    /// Get this weeks lunch menu for the active org, see [`Self::org`]
     ///
     /// # Returns
     /// A [`LunchMenu`] or [`LunchMenuError`] depending on if the request and parsing was
     /// successful
-    pub async fn get_lunch(&mut self) -> Result<LunchMenu, LunchMenuError> {
-        // Get token
-        let token = self
-            .smart_token()
-            .await
-            .map_err(LunchMenuError::TokenError)?;
+    pub async fn get_lunch(&self) -> Result<LunchMenu, LunchMenuError> {
+        let org = self
+            .org()
+            .ok_or(LunchMenuError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_lunch_for(org).await
+    }
 
+    /// Get this weeks lunch menu for a specific org
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lunch",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_lunch_for(&self, org: &Org) -> Result<LunchMenu, LunchMenuError> {
         // Create Request
+        let url = api_for(self, "lunchmenus", org).map_err(LunchMenuError::RequestError)?;
         let request = self
-            .client
-            .get(api(self, "lunchmenus"))
-            .header("token", token.token);
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Lunch);
 
-        // Get menu
-        let response = make_request(request)
-            .await
-            .map_err(LunchMenuError::RequestError)?;
+        // Get menu, a token is only needed if it isn't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self
+                    .smart_token()
+                    .await
+                    .map_err(LunchMenuError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(LunchMenuError::RequestError)?
+            }
+        };
 
         // Deserialize and return
-        LunchMenu::deserialize(&response).map_err(LunchMenuError::ParseError)
+        instrument::parse("lunch", || LunchMenu::deserialize(&response))
+            .map_err(LunchMenuError::ParseError)
     }
 
-    /// Get the entire schedule (cus schoolsoft doesn't believe in the concept of filters)
+    /// Get the news for the active org, see [`Self::org`]
+    ///
+    /// # Returns
+    /// The [`NewsItem`]s currently shown to the user or [`NewsError`] depending on if the request
+    /// and parsing was successful
+    #[cfg(feature = "unverified")]
+    pub async fn get_news(&self) -> Result<Vec<NewsItem>, NewsError> {
+        let org = self
+            .org()
+            .ok_or(NewsError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_news_for(org).await
+    }
+
+    /// Get the news for a specific org
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "news",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_news_for(&self, org: &Org) -> Result<Vec<NewsItem>, NewsError> {
+        // Create Request
+        let url = api_for(self, "news", org).map_err(NewsError::RequestError)?;
+        let request = self
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::News);
+
+        // Get news, a token is only needed if they aren't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self.smart_token().await.map_err(NewsError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(NewsError::RequestError)?
+            }
+        };
+
+        // Deserialize and return
+        instrument::parse("news", || NewsItem::deserialize_many(&response))
+            .map_err(NewsError::ParseError)
+    }
+
+    /// Get the assignments, tests and deadlines planned in the active org, see [`Self::org`]
+    ///
+    /// # Returns
+    /// Every [`PlanningItem`] schoolsoft has for the user or [`PlanningError`] depending on if the
+    /// request and parsing was successful
+    #[cfg(feature = "unverified")]
+    pub async fn get_planning(&self) -> Result<Vec<PlanningItem>, PlanningError> {
+        let org = self
+            .org()
+            .ok_or(PlanningError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_planning_for(org).await
+    }
+
+    /// Get the assignments, tests and deadlines planned in a specific org
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "planning",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_planning_for(&self, org: &Org) -> Result<Vec<PlanningItem>, PlanningError> {
+        // Create Request
+        let url = api_for(self, "planning", org).map_err(PlanningError::RequestError)?;
+        let request = self
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Planning);
+
+        // Get planning, a token is only needed if they aren't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self
+                    .smart_token()
+                    .await
+                    .map_err(PlanningError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(PlanningError::RequestError)?
+            }
+        };
+
+        // Deserialize and return
+        instrument::parse("planning", || PlanningItem::deserialize_many(&response))
+            .map_err(PlanningError::ParseError)
+    }
+
+    /// Get the grades of the student in the active org, see [`Self::org`]
     ///
-    pub async fn get_schedule(&mut self) -> Result<Schedule, ScheduleError> {
+    /// Parents get the grades of the selected child, see [`Self::select_child`].
+    ///
+    /// # Returns
+    /// A [`Grade`] for every course that has been graded or [`GradesError`] depending on if the
+    /// request and parsing was successful
+    #[cfg(feature = "unverified")]
+    pub async fn get_grades(&self) -> Result<Vec<Grade>, GradesError> {
+        let org = self
+            .org()
+            .ok_or(GradesError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_grades_for(org).await
+    }
+
+    /// Get the grades of the student in a specific org
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "grades",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_grades_for(&self, org: &Org) -> Result<Vec<Grade>, GradesError> {
+        // Create Request
+        let url = api_for(self, "grades", org).map_err(GradesError::RequestError)?;
+        let request = self
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Grades);
+
+        // Get grades, a token is only needed if they aren't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self.smart_token().await.map_err(GradesError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(GradesError::RequestError)?
+            }
+        };
+
+        // Deserialize and return
+        instrument::parse("grades", || Grade::deserialize_many(&response))
+            .map_err(GradesError::ParseError)
+    }
+
+    /// Get the absences registered for the student in the active org, see [`Self::org`]
+    ///
+    /// Parents get the absences of the selected child, see [`Self::select_child`].
+    ///
+    /// # Returns
+    /// Every [`Absence`] schoolsoft has for the student or [`AbsenceError`] depending on if the
+    /// request and parsing was successful
+    #[cfg(feature = "unverified")]
+    pub async fn get_absences(&self) -> Result<Vec<Absence>, AbsenceError> {
+        let org = self
+            .org()
+            .ok_or(AbsenceError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_absences_for(org).await
+    }
+
+    /// Get the absences registered for the student in a specific org
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "absences",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_absences_for(&self, org: &Org) -> Result<Vec<Absence>, AbsenceError> {
+        // Create Request
+        let url = api_for(self, "absences", org).map_err(AbsenceError::RequestError)?;
+        let request = self
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Absences);
+
+        // Get absences, a token is only needed if they aren't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self.smart_token().await.map_err(AbsenceError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(AbsenceError::RequestError)?
+            }
+        };
+
+        // Deserialize and return
+        instrument::parse("absences", || Absence::deserialize_many(&response))
+            .map_err(AbsenceError::ParseError)
+    }
+
+    /// Report absence for the active org, see [`crate::report`]
+    ///
+    /// The report is checked against `schedule` first, see [`AbsenceReport::validate`]. Parents
+    /// report absence for the selected child, see [`Self::select_child`].
+    ///
+    /// The request is never retried, even with a [`crate::retry::RetryPolicy`], so a report that
+    /// fails half way isn't registered twice.
+    ///
+    /// # Returns
+    /// If schoolsoft accepted or rejected the report, or the request that would have been sent if
+    /// [`AbsenceReport::dry_run`] is set
+    #[cfg(feature = "unverified")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "report_absence",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, dry_run = report.dry_run)
+        )
+    )]
+    pub async fn report_absence(
+        &self,
+        report: &AbsenceReport,
+        schedule: &Schedule,
+    ) -> Result<ReportOutcome, ReportError> {
+        report
+            .validate(self, schedule)
+            .map_err(ReportError::InvalidReport)?;
+
+        // Create Request
+        let org = self
+            .org()
+            .ok_or(ReportError::RequestError(RequestError::NoOrganization))?;
+        let url = api_for(self, "absences", org).map_err(ReportError::RequestError)?;
+        let request = self
+            .request(reqwest::Method::POST, url)
+            .form(&report.form());
+
+        if report.dry_run {
+            return Ok(ReportOutcome::DryRun(request));
+        }
+
         // Get token
-        let token = self
-            .smart_token()
+        let token = self.smart_token().await.map_err(ReportError::TokenError)?;
+
+        // Send report
+        let response = match self
+            .http
+            .send_once(request.header("token", token.token.expose()))
             .await
-            .map_err(ScheduleError::TokenError)?;
+        {
+            Ok(response) => response,
+            Err(RequestError::UncheckedCode(status, body))
+                if crate::report::REJECTED_STATUSES.contains(&status) =>
+            {
+                // Refusals might have the same body as the ones with a successful status code
+                return Ok(match ReportOutcome::deserialize(&body) {
+                    Ok(ReportOutcome::Rejected(message)) => ReportOutcome::Rejected(message),
+                    _ => ReportOutcome::Rejected(body),
+                });
+            }
+            Err(error) => return Err(ReportError::RequestError(error)),
+        };
+
+        // Deserialize and return
+        let outcome = instrument::parse("report_absence", || {
+            ReportOutcome::deserialize(&response.body)
+        })
+        .map_err(ReportError::ParseError)?;
+
+        // Cached absences don't include the new ones
+        if let (ReportOutcome::Accepted(_), Some(cache)) = (&outcome, self.http.cache()) {
+            cache.invalidate(Endpoint::Absences);
+        }
+
+        Ok(outcome)
+    }
 
+    /// Get the entire schedule (cus schoolsoft doesn't believe in the concept of filters)
+    ///
+    /// Only includes lessons from the active org, see [`Self::org`] and
+    /// [`Self::get_all_schedules`].
+    pub async fn get_schedule(&self) -> Result<Schedule, ScheduleError> {
+        let org = self
+            .org()
+            .ok_or(ScheduleError::RequestError(RequestError::NoOrganization))?;
+
+        self.get_schedule_for(org).await
+    }
+
+    /// Get the entire schedule for a specific org
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "schedule",
+            skip_all,
+            fields(school = %self.school_url, user = self.id, org = org.id)
+        )
+    )]
+    pub async fn get_schedule_for(&self, org: &Org) -> Result<Schedule, ScheduleError> {
         // Create request
+        let url = api_for(self, "lessons", org).map_err(ScheduleError::RequestError)?;
         let request = self
-            .client
-            .get(api(self, "lessons"))
-            .header("token", token.token);
+            .request(reqwest::Method::GET, url)
+            .endpoint(Endpoint::Schedule);
 
-        let response = make_request(request)
-            .await
-            .map_err(ScheduleError::RequestError)?;
+        // A token is only needed if the schedule isn't cached
+        let response = match self.http.cached(&request) {
+            Some(response) => response.body,
+            None => {
+                let token = self
+                    .smart_token()
+                    .await
+                    .map_err(ScheduleError::TokenError)?;
+
+                self.http
+                    .text(request.header("token", token.token.expose()))
+                    .await
+                    .map_err(ScheduleError::RequestError)?
+            }
+        };
 
-        Schedule::deserialize(&response).map_err(ScheduleError::ParseError)
+        instrument::parse("schedule", || {
+            Schedule::deserialize_with_clock(&response, self.clock.as_ref())
+        })
+        .map_err(ScheduleError::ParseError)
+    }
+
+    /// Get the schedules of all orgs the user is a part of merged into one
+    ///
+    /// Each lesson keeps the id of the org it came from in [`crate::schedule::Lesson::org_id`].
+    /// The schedules are fetched one at a time and the first error is returned.
+    pub async fn get_all_schedules(&self) -> Result<Schedule, ScheduleError> {
+        let mut orgs = self.orgs.iter();
+
+        let first = orgs
+            .next()
+            .ok_or(ScheduleError::RequestError(RequestError::NoOrganization))?;
+        let mut schedule = self.get_schedule_for(first).await?;
+
+        for org in orgs {
+            schedule.merge(self.get_schedule_for(org).await?);
+        }
+
+        Ok(schedule)
     }
 }
 
 impl Token {
+    /// Create a new token that uses the [`SystemClock`] to check if it has expired
+    ///
+    /// # Example
+    /// ```
+    /// # use schoolsoft::types::Token;
+    /// let token = Token::new(
+    ///    "notrealtoken123_1337_1".to_string(),
+    ///    chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+    /// );
+    /// ```
+    pub fn new(token: impl Into<Secret>, expires: chrono::NaiveDateTime) -> Token {
+        Self::new_with_clock(token, expires, Arc::new(SystemClock))
+    }
+
+    /// Create a new token that uses a custom [`Clock`] to check if it has expired
+    ///
+    /// # Example
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use schoolsoft::types::Token;
+    /// # use schoolsoft::clock::FixedClock;
+    /// # use chrono::{Duration, NaiveDate};
+    /// let clock = Arc::new(FixedClock::new(
+    ///     NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(11, 0, 0).unwrap(),
+    /// ));
+    ///
+    /// let token = Token::new_with_clock(
+    ///     "notrealtoken123_1337_1".to_string(),
+    ///     NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+    ///     clock.clone(),
+    /// );
+    /// assert!(token.is_valid());
+    ///
+    /// clock.advance(Duration::hours(2));
+    /// assert!(token.is_expired());
+    /// ```
+    pub fn new_with_clock(
+        token: impl Into<Secret>,
+        expires: chrono::NaiveDateTime,
+        clock: Arc<dyn Clock>,
+    ) -> Token {
+        Token {
+            clock,
+            token: token.into(),
+            expires,
+        }
+    }
+
     /// Create a new token with a custom now function
     ///
-    /// This is useful for testing as it allows you to set the current time to a fixed value
+    /// This is useful for testing as it allows you to set the current time to a fixed value.
+    /// Shorthand for [`Self::new_with_clock`] with a plain function as the clock.
     ///
     /// # Example
     /// ```
@@ -137,15 +890,11 @@ impl Token {
     /// ```
     ///
     pub fn new_with_now(
-        token: String,
+        token: impl Into<Secret>,
         expires: chrono::NaiveDateTime,
         now: fn() -> chrono::NaiveDateTime,
     ) -> Token {
-        Token {
-            now,
-            token,
-            expires,
-        }
+        Self::new_with_clock(token, expires, Arc::new(now))
     }
 
     /// Returns the duration until the token expires
@@ -164,7 +913,7 @@ impl Token {
     /// ```
     ///
     pub fn expires_in(&self) -> Duration {
-        self.expires - (self.now)()
+        self.expires - self.clock.now_utc()
     }
 
     /// Check if the token has expired
@@ -181,7 +930,7 @@ impl Token {
     /// ```
     ///
     pub fn is_expired(&self) -> bool {
-        self.expires < (self.now)()
+        self.expires < self.clock.now_utc()
     }
 
     /// Check if the token is still valid
@@ -233,6 +982,15 @@ impl Token {
     }
 }
 
+/// Tokens are compared by value, the clock is ignored
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.token == other.token && self.expires == other.expires
+    }
+}
+
+impl Eq for Token {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,8 +1029,8 @@ mod tests {
                 "pictureFile.jsp?studentId=1337".to_string()
             );
             assert!(!user.is_of_age);
-            assert_eq!(user.app_key, "123notreal".to_string());
-            assert_eq!(user.token, None);
+            assert_eq!(user.app_key().expose(), "123notreal");
+            assert_eq!(user.token(), None);
             assert_eq!(user.user_type, UserType::Student);
             assert_eq!(user.id, 1337);
             assert_eq!(user.orgs.len(), 1);
@@ -293,6 +1051,7 @@ mod tests {
 
     mod token {
         use super::*;
+        use crate::clock::FixedClock;
 
         #[test]
         fn valid_triple_decimal() {
@@ -304,7 +1063,7 @@ mod tests {
             )
             .expect("Failed to deserialize JSON with 3 decimal places");
 
-            assert_eq!(token.token, "123notrealtoken123_1337_1".to_string());
+            assert_eq!(token.token.expose(), "123notrealtoken123_1337_1");
             assert_eq!(
                 token.expires,
                 chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
@@ -325,7 +1084,7 @@ mod tests {
             )
             .expect("Failed to deserialize JSON with 2 decimal places");
 
-            assert_eq!(token.token, "123notrealtoken123_1337_1".to_string());
+            assert_eq!(token.token.expose(), "123notrealtoken123_1337_1");
             assert_eq!(
                 token.expires,
                 chrono::NaiveDate::from_ymd_opt(2024, 2, 6)
@@ -346,7 +1105,7 @@ mod tests {
             )
             .expect("Failed to deserialize JSON with 1 decimal place");
 
-            assert_eq!(token.token, "123notrealtoken123_1337_1".to_string());
+            assert_eq!(token.token.expose(), "123notrealtoken123_1337_1");
             assert_eq!(
                 token.expires,
                 chrono::NaiveDate::from_ymd_opt(2024, 2, 6)
@@ -367,7 +1126,7 @@ mod tests {
             )
             .expect("Failed to deserialize JSON with no decimal places");
 
-            assert_eq!(token.token, "123notrealtoken123_1337_1".to_string());
+            assert_eq!(token.token.expose(), "123notrealtoken123_1337_1");
             assert_eq!(
                 token.expires,
                 chrono::NaiveDate::from_ymd_opt(2024, 2, 6)
@@ -387,12 +1146,12 @@ mod tests {
             )
             .expect("Failed to deserialize JSON");
 
-            token.now = || {
+            token.clock = Arc::new(FixedClock::new(
                 chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
                     .unwrap()
                     .and_hms_opt(11, 0, 0)
-                    .unwrap()
-            };
+                    .unwrap(),
+            ));
 
             assert_eq!(token.expires_in(), chrono::Duration::hours(1));
         }
@@ -407,12 +1166,12 @@ mod tests {
             )
             .expect("Failed to deserialize JSON");
 
-            token.now = || {
+            token.clock = Arc::new(FixedClock::new(
                 chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
                     .unwrap()
                     .and_hms_opt(11, 59, 0)
-                    .unwrap()
-            };
+                    .unwrap(),
+            ));
 
             assert_eq!(token.expires_in(), chrono::Duration::minutes(1));
             assert!(!token.is_expired());
@@ -429,18 +1188,51 @@ mod tests {
             )
             .expect("Failed to deserialize JSON");
 
-            token.now = || {
+            token.clock = Arc::new(FixedClock::new(
                 chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
                     .unwrap()
                     .and_hms_opt(12, 0, 0)
-                    .unwrap()
-            };
+                    .unwrap(),
+            ));
 
             assert_eq!(token.expires_in(), chrono::Duration::zero());
             assert!(!token.is_expired());
             assert!(token.is_valid());
         }
 
+        /// Schoolsoft sends expiry dates in UTC, whatever the local time is
+        #[test]
+        fn expiration_utc() {
+            #[derive(Debug)]
+            struct Stockholm;
+
+            impl Clock for Stockholm {
+                fn now(&self) -> chrono::NaiveDateTime {
+                    self.now_utc() + chrono::Duration::hours(1)
+                }
+
+                fn now_utc(&self) -> chrono::NaiveDateTime {
+                    chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+                        .unwrap()
+                        .and_hms_opt(11, 0, 0)
+                        .unwrap()
+                }
+            }
+
+            let mut token = Token::deserialize(
+                r#"{
+                    "expiryDate":"2024-01-01 12:00:00",
+                    "token":"123notrealtoken123_1337_1"
+                }"#,
+            )
+            .expect("Failed to deserialize JSON");
+
+            token.clock = Arc::new(Stockholm);
+
+            assert_eq!(token.expires_in(), chrono::Duration::hours(1));
+            assert!(token.is_valid());
+        }
+
         #[test]
         fn expiration_expired() {
             let mut token = Token::deserialize(
@@ -451,16 +1243,16 @@ mod tests {
             )
             .expect("Failed to deserialize JSON");
 
-            token.now = || {
+            token.clock = Arc::new(FixedClock::new(
                 chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
                     .unwrap()
                     .and_hms_opt(12, 0, 1)
-                    .unwrap()
-            };
+                    .unwrap(),
+            ));
 
             assert_eq!(token.expires_in(), chrono::Duration::seconds(-1));
-            assert_eq!(token.is_expired(), true);
-            assert_eq!(token.is_valid(), false);
+            assert!(token.is_expired());
+            assert!(!token.is_valid());
         }
     }
 }
@@ -1,30 +1,207 @@
 use std::{ops::Range, str::Chars};
 
+#[cfg(feature = "unverified")]
+use crate::types::error::{
+    AbsenceError, ChildrenError, GradesError, NewsError, PlanningError, ReportError,
+};
 use crate::{
-    types::error::RequestError,
-    user::{User, UserType},
+    types::error::{
+        Error, ErrorKind, LoginError, LunchMenuError, RequestError, ScheduleError,
+        SchoolListingError, TokenError, WebSessionError,
+    },
+    user::{Org, User, UserType},
 };
 use reqwest::StatusCode;
 
-pub fn check_codes(code: StatusCode) -> Result<(), RequestError> {
+/// How many characters of the response body are kept in a [`RequestError`]
+pub const BODY_SNIPPET_LENGTH: usize = 200;
+
+/// Check the status code of a response, keeping the start of `body` in the error
+pub fn check_codes(code: StatusCode, body: &str) -> Result<(), RequestError> {
     if code.is_success() {
         return Ok(());
     }
 
     match code {
-        StatusCode::UNAUTHORIZED => Err(RequestError::Unauthorized),
-        StatusCode::INTERNAL_SERVER_ERROR => Err(RequestError::InternalServerError),
-        _ => Err(RequestError::UncheckedCode(code)),
+        StatusCode::UNAUTHORIZED => Err(RequestError::Unauthorized(snippet(body))),
+        StatusCode::INTERNAL_SERVER_ERROR => Err(RequestError::InternalServerError(snippet(body))),
+        _ => Err(RequestError::UncheckedCode(code, snippet(body))),
     }
 }
 
-pub async fn make_request(regeuest: reqwest::RequestBuilder) -> Result<String, RequestError> {
-    let response = regeuest.send().await.map_err(RequestError::RequestError)?;
-    let code = response.status();
-    check_codes(code)?;
+/// Get the first [`BODY_SNIPPET_LENGTH`] characters of a response body
+fn snippet(body: &str) -> String {
+    body.trim().chars().take(BODY_SNIPPET_LENGTH).collect()
+}
+
+impl RequestError {
+    /// Get the status code of the response that caused the error, if there was one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            RequestError::Unauthorized(_) => Some(StatusCode::UNAUTHORIZED),
+            RequestError::InternalServerError(_) => Some(StatusCode::INTERNAL_SERVER_ERROR),
+            RequestError::UncheckedCode(code, _) => Some(*code),
+            RequestError::RequestError(error) | RequestError::ReadError(error) => error.status(),
+            _ => None,
+        }
+    }
+
+    /// Get the start of the body of the response that caused the error, if there was one
+    ///
+    /// At most [`BODY_SNIPPET_LENGTH`] characters are kept.
+    pub fn body(&self) -> Option<&str> {
+        let body = match self {
+            RequestError::Unauthorized(body)
+            | RequestError::InternalServerError(body)
+            | RequestError::UncheckedCode(_, body) => body,
+            _ => return None,
+        };
+
+        (!body.is_empty()).then_some(body.as_str())
+    }
 
-    let data = response.text().await.map_err(RequestError::ReadError)?;
-    Ok(data)
+    /// Get what kind of error this is
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RequestError::RequestError(_)
+            | RequestError::ReadError(_)
+            | RequestError::TransportError(_) => ErrorKind::Connection,
+            RequestError::Unauthorized(_) => ErrorKind::Auth,
+            RequestError::RateLimited(_) => ErrorKind::RateLimited,
+            RequestError::InternalServerError(_) => ErrorKind::Server,
+            RequestError::UncheckedCode(StatusCode::TOO_MANY_REQUESTS, _) => ErrorKind::RateLimited,
+            RequestError::UncheckedCode(code, _) if code.is_server_error() => ErrorKind::Server,
+            RequestError::UncheckedCode(_, _) => ErrorKind::Status,
+            RequestError::NoChildSelected
+            | RequestError::ParentsUnsupported
+            | RequestError::NoOrganization
+            | RequestError::UnknownOrganization(_) => ErrorKind::InvalidRequest,
+        }
+    }
+}
+
+impl ErrorKind {
+    /// Check if a request that failed like this might work if it is sent again later
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::Connection | ErrorKind::Server | ErrorKind::RateLimited
+        )
+    }
+}
+
+impl Error {
+    /// Get the [`RequestError`] that caused the error, if any
+    ///
+    /// Looks through token errors and logging in again, so an app key that was rejected while
+    /// getting the lunch menu still gives [`RequestError::Unauthorized`].
+    pub fn request_error(&self) -> Option<&RequestError> {
+        fn login(error: &LoginError) -> Option<&RequestError> {
+            match error {
+                LoginError::RequestError(error) => Some(error),
+                _ => None,
+            }
+        }
+
+        fn token(error: &TokenError) -> Option<&RequestError> {
+            match error {
+                TokenError::RequestError(error) => Some(error),
+                TokenError::LoginError(error) => login(error),
+                TokenError::ParseError(_) => None,
+            }
+        }
+
+        match self {
+            Error::Request(error)
+            | Error::SchoolListing(SchoolListingError::RequestError(error))
+            | Error::WebSession(WebSessionError::RequestError(error))
+            | Error::LunchMenu(LunchMenuError::RequestError(error))
+            | Error::Schedule(ScheduleError::RequestError(error)) => Some(error),
+            Error::Login(error) => login(error),
+            #[cfg(feature = "unverified")]
+            Error::Children(ChildrenError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::Children(ChildrenError::TokenError(error)) => token(error),
+            #[cfg(feature = "unverified")]
+            Error::Planning(PlanningError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::Planning(PlanningError::TokenError(error)) => token(error),
+            #[cfg(feature = "unverified")]
+            Error::News(NewsError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::News(NewsError::TokenError(error)) => token(error),
+            #[cfg(feature = "unverified")]
+            Error::Grades(GradesError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::Grades(GradesError::TokenError(error)) => token(error),
+            #[cfg(feature = "unverified")]
+            Error::Absence(AbsenceError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::Absence(AbsenceError::TokenError(error)) => token(error),
+            #[cfg(feature = "unverified")]
+            Error::Report(ReportError::RequestError(error)) => Some(error),
+            #[cfg(feature = "unverified")]
+            Error::Report(ReportError::TokenError(error)) => token(error),
+            Error::Token(error)
+            | Error::WebSession(WebSessionError::TokenError(error))
+            | Error::LunchMenu(LunchMenuError::TokenError(error))
+            | Error::Schedule(ScheduleError::TokenError(error)) => token(error),
+            _ => None,
+        }
+    }
+
+    /// Get what kind of error this is
+    ///
+    /// # Examples
+    /// ```
+    /// # use schoolsoft::{Error, ErrorKind, types::error::{LunchMenuError, RequestError}};
+    /// let error = Error::from(LunchMenuError::RequestError(RequestError::Unauthorized(
+    ///     "Invalid token".to_string(),
+    /// )));
+    ///
+    /// assert_eq!(error.kind(), ErrorKind::Auth);
+    /// assert!(error.is_auth());
+    /// assert!(!error.is_retryable());
+    /// assert_eq!(error.status().map(|status| status.as_u16()), Some(401));
+    /// assert_eq!(error.body(), Some("Invalid token"));
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(error) = self.request_error() {
+            return error.kind();
+        }
+
+        match self {
+            Error::Login(LoginError::AppLoginUnsupported(_))
+            | Error::SchoolListing(SchoolListingError::BadUrl) => ErrorKind::InvalidRequest,
+            #[cfg(feature = "unverified")]
+            Error::Children(ChildrenError::NotAParent)
+            | Error::Report(ReportError::InvalidReport(_)) => ErrorKind::InvalidRequest,
+            Error::Session(_) => ErrorKind::Session,
+            _ => ErrorKind::Parse,
+        }
+    }
+
+    /// Check if the request might work if it is sent again later, see [`ErrorKind::is_retryable`]
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    /// Check if schoolsoft rejected the password, app key or token
+    pub fn is_auth(&self) -> bool {
+        self.kind() == ErrorKind::Auth
+    }
+
+    /// Get the status code of the response that caused the error, if there was one
+    pub fn status(&self) -> Option<StatusCode> {
+        self.request_error().and_then(RequestError::status)
+    }
+
+    /// Get the start of the body of the response that caused the error, if there was one
+    ///
+    /// See [`RequestError::body`].
+    pub fn body(&self) -> Option<&str> {
+        self.request_error().and_then(RequestError::body)
+    }
 }
 
 pub fn parse_date(raw: &str) -> Result<chrono::NaiveDate, chrono::ParseError> {
@@ -35,14 +212,45 @@ pub fn parse_datetime(raw: &str) -> Result<chrono::NaiveDateTime, chrono::ParseE
     chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f")
 }
 
-pub fn api(user: &User, path: &str) -> String {
-    format!(
+/// Build the url for an api route for `org`, usually the active org, see [`User::org`]
+///
+/// Routes look like `/[school]/api/[path]/[user type]/[org id]`. With the `unverified` feature,
+/// requests made by parents are on behalf of the selected child, whose id is added to the end.
+///
+/// # Errors
+/// - [`RequestError::NoChildSelected`] if the user is a parent without a selected child, only with
+///   the `unverified` feature
+/// - [`RequestError::ParentsUnsupported`] if the user is a parent, only without the `unverified`
+///   feature
+pub fn api_for(user: &User, path: &str, org: &Org) -> Result<String, RequestError> {
+    let url = format!(
         "{}/api/{}/{}/{}",
-        user.school_url,
-        path,
-        user.user_type.to_string(),
-        user.orgs[0].id
-    )
+        user.school_url, path, user.user_type, org.id
+    );
+
+    // The child id suffix hasn't been seen in a captured request yet
+    #[cfg(feature = "unverified")]
+    match (user.user_type, &user.child) {
+        (UserType::Parent, Some(child)) => Ok(format!("{}/{}", url, child.id)),
+        (UserType::Parent, None) => Err(RequestError::NoChildSelected),
+        _ => Ok(url),
+    }
+
+    #[cfg(not(feature = "unverified"))]
+    match user.user_type {
+        UserType::Parent => Err(RequestError::ParentsUnsupported),
+        _ => Ok(url),
+    }
+}
+
+/// Get the name of the school from a school url, e.g `school` from
+/// `https://sms.schoolsoft.se/school`
+pub fn school_name(school_url: &str) -> Option<String> {
+    reqwest::Url::parse(school_url)
+        .ok()?
+        .path_segments()?
+        .rfind(|segment| !segment.is_empty())
+        .map(str::to_string)
 }
 
 pub struct WeekRange<'a> {
@@ -104,14 +312,13 @@ impl Iterator for WeekRange<'_> {
     }
 }
 
-impl ToString for UserType {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for UserType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             UserType::Student => "student",
             UserType::Parent => "parent",
             UserType::Teacher => "teacher",
-        }
-        .to_string()
+        })
     }
 }
 
@@ -122,6 +329,18 @@ macro_rules! rest {
     };
 }
 
+#[cfg(test)]
+mod school_name {
+    use super::school_name;
+
+    #[test]
+    fn from_url() {
+        assert_eq!(school_name("https://sms.schoolsoft.se/school").as_deref(), Some("school"));
+        assert_eq!(school_name("https://sms.schoolsoft.se/school/").as_deref(), Some("school"));
+        assert_eq!(school_name("https://sms.schoolsoft.se"), None);
+    }
+}
+
 #[cfg(test)]
 mod url_macros {
     #[test]
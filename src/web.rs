@@ -0,0 +1,222 @@
+//! Access to the parts of schoolsoft that are only available through the website
+//!
+//! Every [`Org`] has a [`Org::token_login`] url. Replacing [`TOKEN_PLACEHOLDER`] with a token from
+//! the app api and opening it logs in to the website without a password. A [`WebSession`] does
+//! that and keeps the cookies it gets, so it can fetch any page under `/[school]/jsp/` afterwards.
+
+use std::sync::Arc;
+
+use reqwest::Url;
+
+use crate::{
+    clock::SystemClock,
+    device::DeviceProfile,
+    http::{Http, Request},
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    transport::{HttpConfig, HttpTransport, ReqwestTransport},
+    types::error::{RequestError, WebSessionError},
+    user::{Org, User},
+    utils::school_name,
+};
+
+/// The part of [`Org::token_login`] that should be replaced with a token
+pub const TOKEN_PLACEHOLDER: &str = "TOKEN_PLACEHOLDER";
+
+/// A logged in session on the schoolsoft website
+///
+/// Created with [`User::web_session`]. The session lives as long as schoolsoft keeps the cookies
+/// valid, create a new one if pages stop loading.
+///
+/// Pages are requested the same way as the app api, with the retry policy, rate limit and
+/// middleware of the user that created the session. Only the transport is different, since the
+/// website needs one that keeps cookies, see [`WebSession::transport`].
+#[derive(Debug, Clone)]
+pub struct WebSession {
+    http: Http,
+    school_url: Url,
+}
+
+impl WebSession {
+    /// Create a transport that keeps every cookie it gets and follows redirects, like a browser
+    ///
+    /// Only the user agent of `device` is used, the website doesn't care about the other headers.
+    pub fn transport(
+        device: &DeviceProfile,
+        config: &HttpConfig,
+    ) -> Result<ReqwestTransport, WebSessionError> {
+        let client = config
+            .apply(reqwest::Client::builder())
+            .cookie_store(true)
+            .user_agent(&device.user_agent)
+            .build()
+            .map_err(|e| WebSessionError::RequestError(RequestError::RequestError(e)))?;
+
+        Ok(ReqwestTransport::new(client))
+    }
+
+    /// Login to the website using `token` and the token login url of `org`
+    ///
+    /// All redirects are followed and every cookie set along the way is kept.
+    pub async fn login(
+        school_url: Url,
+        org: &Org,
+        token: &str,
+        device: &DeviceProfile,
+    ) -> Result<WebSession, WebSessionError> {
+        Self::login_with_config(school_url, org, token, device, &HttpConfig::default()).await
+    }
+
+    /// Same as [`Self::login`] but with the timeouts, proxies and root certificates in `config`
+    ///
+    /// Requests are retried with the default [`RetryPolicy`], limited by the default
+    /// [`RateLimiter`] and use the [`SystemClock`].
+    pub async fn login_with_config(
+        school_url: Url,
+        org: &Org,
+        token: &str,
+        device: &DeviceProfile,
+        config: &HttpConfig,
+    ) -> Result<WebSession, WebSessionError> {
+        let http = Http::new(
+            Arc::new(Self::transport(device, config)?),
+            RetryPolicy::default(),
+            RateLimiter::default(),
+            None,
+            Vec::new(),
+            config.clone(),
+            Arc::new(SystemClock),
+        );
+
+        Self::login_with_http(school_url, org, token, http).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "web_login", skip_all, fields(school = %school_url, org = org.id))
+    )]
+    async fn login_with_http(
+        school_url: Url,
+        org: &Org,
+        token: &str,
+        http: Http,
+    ) -> Result<WebSession, WebSessionError> {
+        let session = WebSession {
+            http,
+            school_url: page_url(school_url, &org.token_login),
+        };
+        let url = org.token_login.replace(TOKEN_PLACEHOLDER, token);
+
+        session
+            .http
+            .text(session.request(url))
+            .await
+            .map_err(WebSessionError::RequestError)?;
+
+        Ok(session)
+    }
+
+    /// Get the url of the school the session is logged in to
+    ///
+    /// This is on the host of the token login url, which can differ from the one the app api is
+    /// used through, since that is where the session cookies are set.
+    pub fn school_url(&self) -> &Url {
+        &self.school_url
+    }
+
+    /// Get a page from the website
+    ///
+    /// `path` is relative to the school, e.g `jsp/student/right_student_startpage.jsp`. A leading
+    /// `/` is ignored.
+    ///
+    /// # Returns
+    /// The body of the page, usually html.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "web",
+            skip_all,
+            fields(school = %self.school_url, path = crate::instrument::redact_url(path))
+        )
+    )]
+    pub async fn get(&self, path: &str) -> Result<String, RequestError> {
+        let url = format!(
+            "{}/{}",
+            self.school_url.as_str().trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+
+        self.http.text(self.request(url)).await
+    }
+
+    /// Create a request for the school of the session, so it shares its rate limit
+    fn request(&self, url: String) -> Request {
+        let request = Request::get(url);
+
+        match school_name(self.school_url.as_str()) {
+            Some(school) => request.school(school),
+            None => request,
+        }
+    }
+}
+
+/// Move `school_url` to the scheme, host and port of `token_login`
+///
+/// The website sets its cookies on the host of the token login url, like `sms1.schoolsoft.se`,
+/// so pages have to be requested from there. `school_url` is kept as it is if `token_login` isn't
+/// a valid url.
+fn page_url(mut school_url: Url, token_login: &str) -> Url {
+    let Ok(login) = Url::parse(token_login) else {
+        return school_url;
+    };
+
+    // Only fails for urls that can't have a host, which a token login url always has
+    let _ = school_url.set_scheme(login.scheme());
+    let _ = school_url.set_host(login.host_str());
+    let _ = school_url.set_port(login.port());
+
+    school_url
+}
+
+impl User {
+    /// Login to the website for the active org, see [`User::org`]
+    ///
+    /// Uses [`User::smart_token`] to get the token needed to login.
+    pub async fn web_session(&self) -> Result<WebSession, WebSessionError> {
+        let org = self
+            .org()
+            .ok_or(WebSessionError::RequestError(RequestError::NoOrganization))?;
+
+        self.web_session_for(org).await
+    }
+
+    /// Login to the website for a specific org
+    pub async fn web_session_for(&self, org: &Org) -> Result<WebSession, WebSessionError> {
+        let transport = WebSession::transport(&self.device, self.http.config())?;
+        self.web_session_with_transport(org, transport).await
+    }
+
+    /// Login to the website for a specific org, sending every request with `transport`
+    ///
+    /// The transport has to keep cookies and follow redirects, like the one created by
+    /// [`WebSession::transport`]. Wrap that in a [`crate::cassette::RecordingTransport`] to record
+    /// the session.
+    pub async fn web_session_with_transport(
+        &self,
+        org: &Org,
+        transport: impl HttpTransport + 'static,
+    ) -> Result<WebSession, WebSessionError> {
+        let token = self
+            .smart_token()
+            .await
+            .map_err(WebSessionError::TokenError)?;
+
+        WebSession::login_with_http(
+            self.school_url.clone(),
+            org,
+            token.token.expose(),
+            self.http.with_transport(Arc::new(transport)),
+        )
+        .await
+    }
+}
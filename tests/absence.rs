@@ -0,0 +1,134 @@
+#![cfg(feature = "unverified")]
+
+use chrono::NaiveDate;
+use schoolsoft::types::{
+    error::{AbsenceError, AbsenceParseError, RequestError},
+    AbsenceScope, Reporter, UserType,
+};
+
+use crate::mock::{basic_user_with_token, get};
+
+mod mock;
+
+const ABSENCES: &str = r#"[
+    {
+        "id": 9001,
+        "date": "2024-02-12",
+        "lessonId": 1234,
+        "length": 60,
+        "reason": "Sjuk",
+        "valid": true,
+        "creByType": 2
+    },
+    {
+        "id": 9002,
+        "date": "2024-02-13",
+        "lessonId": 0,
+        "length": 480,
+        "reason": "",
+        "valid": false,
+        "creByType": 3
+    },
+    {
+        "id": 9003,
+        "date": "2024-02-14",
+        "length": 40,
+        "valid": true,
+        "creByType": 7
+    }
+]"#;
+
+/// A lesson id of 0, or none at all, means the whole day was missed
+#[tokio::test]
+async fn scope() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/absences/student/1",
+        ABSENCES,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let absences = user.get_absences().await.expect("Failed to get absences");
+
+    mock.assert();
+
+    assert_eq!(absences[0].scope, AbsenceScope::Lesson(1234));
+    assert_eq!(absences[0].minutes, 60);
+    assert_eq!(absences[1].scope, AbsenceScope::WholeDay);
+    assert_eq!(absences[1].minutes, 480);
+    assert_eq!(absences[2].scope, AbsenceScope::WholeDay);
+    assert_eq!(
+        absences[2].date,
+        NaiveDate::from_ymd_opt(2024, 2, 14).unwrap()
+    );
+}
+
+/// `creByType` becomes who reported the absence and empty reasons are left out
+#[tokio::test]
+async fn reason_and_reporter() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/absences/student/1",
+        ABSENCES,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let absences = user.get_absences().await.expect("Failed to get absences");
+
+    mock.assert();
+
+    assert_eq!(absences[0].reason.as_deref(), Some("Sjuk"));
+    assert_eq!(absences[1].reason, None);
+    assert_eq!(absences[2].reason, None);
+
+    assert_eq!(absences[0].reported_by, Reporter::Parent);
+    assert_eq!(absences[1].reported_by, Reporter::Staff);
+    assert_eq!(absences[2].reported_by, Reporter::Other(7));
+
+    assert!(absences[0].valid);
+    assert!(!absences[1].valid);
+}
+
+/// Absence dates never include a time
+#[tokio::test]
+async fn bad_date() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/absences/student/1",
+        r#"[{"id": 1, "date": "2024-02-12 08:00:00.0", "length": 60, "valid": true, "creByType": 1}]"#,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    match user.get_absences().await {
+        Err(AbsenceError::ParseError(AbsenceParseError::DateParseError(date, _))) => {
+            assert_eq!(date, "2024-02-12 08:00:00.0")
+        }
+        other => panic!("Expected a date error, got {:?}", other),
+    }
+
+    mock.assert();
+}
+
+/// Parents have to pick which child to get absences for
+#[tokio::test]
+async fn no_child_selected() {
+    let mut user = basic_user_with_token("http://localhost");
+    user.user_type = UserType::Parent;
+
+    match user.get_absences().await {
+        Err(AbsenceError::RequestError(RequestError::NoChildSelected)) => (),
+        other => panic!("Expected no child selected, got {:?}", other),
+    }
+}
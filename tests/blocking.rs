@@ -0,0 +1,77 @@
+#![cfg(feature = "blocking")]
+
+use schoolsoft::{
+    types::error::{LoginError, RequestError},
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user_with_token, get, get_with_token_and_login, mock_clock};
+
+mod mock;
+
+#[test]
+fn login_and_lunch() {
+    let mut server = mockito::Server::new();
+    let (login, token, lunch) = get_with_token_and_login(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+    );
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build_blocking();
+
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .expect("Login should be successful");
+
+    let user = client
+        .user
+        .as_ref()
+        .expect("User should be set after login");
+    assert_eq!(user.name, "Mock User");
+
+    user.get_lunch().expect("Failed to get lunch");
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+}
+
+#[test]
+fn schedule() {
+    let mut server = mockito::Server::new();
+    let mock = get(
+        &mut server,
+        "api/lessons/student/1",
+        include_str!("../hurl/output/schedule.json"),
+        None,
+    );
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(mock_clock())
+        .build_blocking();
+    let user = client.adopt(basic_user_with_token(&server.url()));
+
+    user.get_schedule().expect("Failed to get schedule");
+    mock.assert();
+}
+
+#[test]
+fn same_errors() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .with_status(401)
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build_blocking();
+
+    match client.login("mock_username", "mock_password", "mock_school") {
+        Err(LoginError::RequestError(RequestError::Unauthorized(_))) => (),
+        other => panic!("Expected Unauthorized, got {:?}", other),
+    }
+
+    assert!(client.user.is_none());
+    mock.assert();
+}
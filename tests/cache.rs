@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use schoolsoft::{
+    cache::{Cache, Endpoint},
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user_with_token, get, mock_clock, token_mock};
+
+mod mock;
+
+const SCHOOLS: &str = r#"[{
+    "studentLoginMethods": "4",
+    "parentLoginMethods": "4",
+    "name": "Mock School",
+    "teacherLoginMethods": "4",
+    "url": "https://sms.schoolsoft.se/mock_school/"
+}]"#;
+
+/// A fresh response is used without asking schoolsoft again
+#[tokio::test]
+async fn cached_until_expired() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .expect(1)
+        .create();
+
+    let clock = mock_clock();
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(clock.clone())
+        .cache(Cache::memory().ttl(Endpoint::Schools, Duration::from_secs(60)))
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    let schools = client
+        .schools()
+        .await
+        .expect("Failed to get cached schools");
+    assert_eq!(schools[0].name, "Mock School");
+    mock.assert();
+
+    // Downloaded again once the response has expired
+    clock.advance(chrono::Duration::minutes(2));
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .expect(1)
+        .create();
+
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+}
+
+/// An expired response is kept if schoolsoft says it hasn't changed
+#[tokio::test]
+async fn not_modified() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_header("etag", "\"v1\"")
+        .with_body(SCHOOLS)
+        .expect(1)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .cache(Cache::memory().ttl(Endpoint::Schools, Duration::ZERO))
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+
+    let not_modified = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    let schools = client
+        .schools()
+        .await
+        .expect("Failed to revalidate schools");
+    assert_eq!(schools[0].name, "Mock School");
+    not_modified.assert();
+}
+
+/// Invalidating a user removes their responses so they are downloaded again
+#[tokio::test]
+async fn invalidate_user() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+        Some("mock_school"),
+    )
+    .expect(2);
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(mock_clock())
+        .cache(Cache::memory())
+        .build();
+
+    let user = client.adopt(basic_user_with_token(&server.url()));
+
+    user.get_lunch().await.expect("Failed to get lunch");
+    user.get_lunch().await.expect("Failed to get cached lunch");
+
+    user.invalidate_cache();
+    user.get_lunch().await.expect("Failed to get lunch");
+
+    mock.assert();
+}
+
+/// Cached responses are used without getting a token first
+#[tokio::test]
+async fn cached_without_token() {
+    let mut server = mockito::Server::new();
+
+    let lunch = get(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+        Some("mock_school"),
+    )
+    .expect(1);
+    let schedule = get(
+        &mut server,
+        "api/lessons/student/1",
+        include_str!("../hurl/output/schedule.json"),
+        Some("mock_school"),
+    )
+    .expect(1);
+    let token = token_mock(&mut server, "one_of_those_tokens", None).expect(0);
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(mock_clock())
+        .cache(Cache::memory())
+        .build();
+
+    let user = client.adopt(basic_user_with_token(&server.url()));
+    user.get_lunch().await.expect("Failed to get lunch");
+    user.get_schedule().await.expect("Failed to get schedule");
+
+    // Without a token the next requests would need a new one, unless they are cached
+    user.set_token(None);
+    user.get_lunch().await.expect("Failed to get cached lunch");
+    user.get_schedule()
+        .await
+        .expect("Failed to get cached schedule");
+
+    lunch.assert();
+    schedule.assert();
+    token.assert();
+}
+
+/// Endpoints behind the `unverified` feature are cached the same way
+#[cfg(feature = "unverified")]
+#[tokio::test]
+async fn unverified_cached_without_token() {
+    let mut server = mockito::Server::new();
+
+    let news = get(&mut server, "api/news/student/1", "[]", None).expect(1);
+    let grades = get(&mut server, "api/grades/student/1", "[]", None).expect(1);
+    let token = token_mock(&mut server, "one_of_those_tokens", None).expect(0);
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(mock_clock())
+        .cache(Cache::memory())
+        .build();
+
+    let user = client.adopt(basic_user_with_token(&server.url()));
+    user.get_news().await.expect("Failed to get news");
+    user.get_grades().await.expect("Failed to get grades");
+
+    user.set_token(None);
+    user.get_news().await.expect("Failed to get cached news");
+    user.get_grades()
+        .await
+        .expect("Failed to get cached grades");
+
+    news.assert();
+    grades.assert();
+    token.assert();
+}
+
+/// Disabled endpoints are always downloaded
+#[tokio::test]
+async fn disabled_endpoint() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .expect(2)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .cache(Cache::memory().disable(Endpoint::Schools))
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+}
+
+/// Responses in a file cache are used by a new client
+#[tokio::test]
+async fn file_cache() {
+    let mut server = mockito::Server::new();
+    let directory =
+        std::env::temp_dir().join(format!("schoolsoft-file-cache-{}", std::process::id()));
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .expect(1)
+        .create();
+
+    for _ in 0..2 {
+        let client = ClientBuilder::new()
+            .base_url(server.url())
+            .cache(Cache::file(&directory))
+            .build();
+
+        client.schools().await.expect("Failed to get schools");
+    }
+    mock.assert();
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use schoolsoft::{
+    cassette::{Cassette, RecordingTransport, ReplayTransport, REDACTED},
+    transport::ReqwestTransport,
+    types::error::{LunchMenuError, RequestError},
+    ClientBuilder,
+};
+
+use crate::mock::{get, login_mock, mock_clock, token_mock};
+
+mod mock;
+
+/// A recorded session can be replayed after the server is gone
+#[tokio::test]
+async fn record_and_replay() {
+    let path =
+        std::env::temp_dir().join(format!("schoolsoft-cassette-{}.json", std::process::id()));
+
+    let mut server = mockito::Server::new();
+    let url = server.url();
+    let login = login_mock(&mut server, None);
+    let token = token_mock(&mut server, "one_of_those_tokens", None);
+    let lunch = get(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+        None,
+    );
+
+    let recorder = Arc::new(RecordingTransport::new(ReqwestTransport::default()));
+    let mut client = ClientBuilder::new()
+        .base_url(url.clone())
+        .transport(recorder.clone())
+        .clock(mock_clock())
+        .build();
+
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+    let recorded = client
+        .user
+        .expect("User should be set after login")
+        .get_lunch()
+        .await
+        .expect("Failed to get lunch");
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+    drop(server);
+
+    let cassette = recorder.cassette();
+    assert_eq!(cassette.interactions.len(), 3);
+    cassette.save(&path).expect("Failed to save cassette");
+
+    // Secrets never end up in the file
+    let data = std::fs::read_to_string(&path).expect("Failed to read cassette");
+    assert!(!data.contains("mock_password"));
+    assert!(!data.contains("123notreal"));
+    assert!(!data.contains("one_of_those_tokens"));
+    assert!(data.contains(REDACTED));
+
+    let replay = Arc::new(ReplayTransport::new(
+        Cassette::load(&path).expect("Failed to load cassette"),
+    ));
+    std::fs::remove_file(&path).expect("Failed to remove cassette");
+
+    // Replay with the real clock, even though the recorded token expired long ago
+    let mut client = ClientBuilder::new()
+        .base_url(url)
+        .transport(replay.clone())
+        .build();
+
+    client
+        .login("mock_username", "another_password", "mock_school")
+        .await
+        .expect("Login should be replayed");
+    let user = client.user.expect("User should be set after login");
+    let replayed = user.get_lunch().await.expect("Lunch should be replayed");
+
+    assert_eq!(recorded, replayed);
+    assert!(replay.is_finished());
+
+    // Every interaction has been used up
+    match user.get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::TransportError(_))) => (),
+        other => panic!("Expected a transport error, got {:?}", other),
+    }
+}
@@ -0,0 +1,67 @@
+use schoolsoft::{device::DeviceProfile, types::UserType, ClientBuilder};
+
+use crate::mock::login_body;
+
+mod mock;
+
+fn device() -> DeviceProfile {
+    DeviceProfile {
+        device_id: "mock_device".to_string(),
+        os: "ios".to_string(),
+        app_version: "9.9.9".to_string(),
+        user_agent: "MockApp/9.9.9".to_string(),
+    }
+}
+
+/// The device profile set on the client is sent when logging in and by the user it creates
+#[tokio::test]
+async fn headers_from_client() {
+    let mut server = mockito::Server::new();
+
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .match_header("deviceid", "mock_device")
+        .match_header("appos", "ios")
+        .match_header("appversion", "9.9.9")
+        .match_header("user-agent", "MockApp/9.9.9")
+        .with_body(login_body(&server.url(), "mock_school", UserType::Student))
+        .create();
+
+    let token = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .match_header("deviceid", "mock_device")
+        .match_header("appos", "ios")
+        .match_header("appversion", "9.9.9")
+        .match_header("user-agent", "MockApp/9.9.9")
+        .with_body(r#"{"expiryDate":"2099-01-01 00:00:00.0","token":"one_of_those_tokens"}"#)
+        .create();
+
+    let mut client = ClientBuilder::new()
+        .base_url(server.url())
+        .device(device())
+        .build();
+
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    let user = client.user.unwrap();
+    assert_eq!(user.device, device());
+
+    user.get_token().await.expect("Getting a token should work");
+
+    login.assert();
+    token.assert();
+}
+
+/// Only the device id is changed when using the old setter
+#[test]
+fn device_id_setter() {
+    let client = ClientBuilder::new()
+        .device_id("1234567890".to_string())
+        .build();
+
+    assert_eq!(client.device_id(), "1234567890");
+    assert_eq!(client.device().os, DeviceProfile::default().os);
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use schoolsoft::{
+    rate_limit::{RateLimit, RateLimiter},
+    types::error::LunchMenuError,
+    utils::BODY_SNIPPET_LENGTH,
+    ClientBuilder, Error, ErrorKind,
+};
+
+use crate::mock::{basic_user, basic_user_with_token, get, token_mock};
+
+mod mock;
+
+/// Errors from different endpoints are classified the same way
+#[tokio::test]
+async fn auth() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .with_status(401)
+        .with_body("Invalid app key")
+        .create();
+
+    let specific = basic_user(&server.url())
+        .get_lunch()
+        .await
+        .expect_err("The app key should be rejected");
+    let error = Error::from(specific);
+
+    assert_eq!(error.kind(), ErrorKind::Auth);
+    assert!(error.is_auth());
+    assert!(!error.is_retryable());
+    assert_eq!(error.status().map(|status| status.as_u16()), Some(401));
+    assert_eq!(error.body(), Some("Invalid app key"));
+
+    // The specific error is kept as the source instead of being repeated in the message
+    assert_eq!(error.to_string(), "Getting the lunch menu failed");
+    let source =
+        std::error::Error::source(&error).expect("The specific error should be the source");
+    assert!(source.downcast_ref::<LunchMenuError>().is_some());
+
+    mock.assert();
+}
+
+/// Only the start of long bodies is kept
+#[tokio::test]
+async fn server_error() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/mock_school/api/lessons/student/1")
+        .with_status(503)
+        .with_body("x".repeat(10_000))
+        .create();
+
+    let error = Error::from(
+        basic_user_with_token(&server.url())
+            .get_schedule()
+            .await
+            .expect_err("The server should fail"),
+    );
+
+    assert_eq!(error.kind(), ErrorKind::Server);
+    assert!(error.is_retryable());
+    assert_eq!(error.body().map(str::len), Some(BODY_SNIPPET_LENGTH));
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn parse_error() {
+    let mut server = mockito::Server::new();
+    let _token = token_mock(&mut server, "one_of_those_tokens", None);
+    let mock = get(&mut server, "api/lunchmenus/student/1", "not json", None);
+
+    let error = Error::from(
+        basic_user(&server.url())
+            .get_lunch()
+            .await
+            .expect_err("The menu should not parse"),
+    );
+
+    assert_eq!(error.kind(), ErrorKind::Parse);
+    assert!(!error.is_retryable());
+    assert_eq!(error.status(), None);
+    assert_eq!(error.body(), None);
+
+    mock.assert();
+}
+
+#[cfg(feature = "unverified")]
+#[tokio::test]
+async fn invalid_request() {
+    let user = basic_user("http://localhost");
+
+    let error = Error::from(
+        user.get_children()
+            .await
+            .expect_err("Students don't have children"),
+    );
+
+    assert_eq!(error.kind(), ErrorKind::InvalidRequest);
+    assert!(!error.is_auth());
+}
+
+/// Errors from the rate limiter count as rate limited
+#[tokio::test]
+async fn rate_limited() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body("[]")
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .rate_limit(
+            RateLimiter::new()
+                .limit(RateLimit::new(1, Duration::from_secs(60)))
+                .wait(false),
+        )
+        .build();
+
+    client.schools().await.expect("First request should fit");
+
+    let error = Error::from(client.schools().await.expect_err("Should be limited"));
+    assert_eq!(error.kind(), ErrorKind::RateLimited);
+    assert!(error.is_retryable());
+}
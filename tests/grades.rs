@@ -0,0 +1,108 @@
+#![cfg(feature = "unverified")]
+
+use chrono::NaiveDate;
+use schoolsoft::types::error::{GradesError, GradesParseError};
+
+use crate::mock::{basic_user_with_token, get};
+
+mod mock;
+
+const GRADES: &str = r#"[
+    {
+        "courseCode": "MATMAT01c",
+        "courseName": "Matematik 1c",
+        "points": 100,
+        "grade": "A",
+        "gradeDate": "2024-01-19 00:00:00.0",
+        "teacherName": "Mock Teacher"
+    },
+    {
+        "courseCode": "GYARAR",
+        "courseName": "Gymnasiearbete",
+        "points": 0,
+        "grade": "E",
+        "gradeDate": ""
+    },
+    {
+        "courseCode": "IDRIDR01",
+        "courseName": "Idrott och hälsa 1",
+        "points": null,
+        "grade": "C",
+        "gradeDate": "2023-06-09",
+        "teacherName": null
+    }
+]"#;
+
+/// Courses without points have 0, null or no points at all
+#[tokio::test]
+async fn points() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/grades/student/1",
+        GRADES,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let grades = user.get_grades().await.expect("Failed to get grades");
+
+    mock.assert();
+
+    assert_eq!(grades[0].points, Some(100));
+    assert_eq!(grades[1].points, None);
+    assert_eq!(grades[2].points, None);
+}
+
+/// Grade dates sometimes include a time, and are empty when schoolsoft doesn't know them
+#[tokio::test]
+async fn dates_and_teacher() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/grades/student/1",
+        GRADES,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let grades = user.get_grades().await.expect("Failed to get grades");
+
+    mock.assert();
+
+    assert_eq!(grades[0].date, NaiveDate::from_ymd_opt(2024, 1, 19));
+    assert_eq!(grades[1].date, None);
+    assert_eq!(grades[2].date, NaiveDate::from_ymd_opt(2023, 6, 9));
+
+    assert_eq!(grades[0].teacher, "Mock Teacher");
+    assert_eq!(grades[1].teacher, "");
+    assert_eq!(grades[2].teacher, "");
+}
+
+/// The date that couldn't be parsed is kept in the error
+#[tokio::test]
+async fn bad_date() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/grades/student/1",
+        r#"[{"courseCode": "GYARAR", "courseName": "", "grade": "E", "gradeDate": "VT24"}]"#,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    match user.get_grades().await {
+        Err(GradesError::ParseError(GradesParseError::DateParseError(date, _))) => {
+            assert_eq!(date, "VT24")
+        }
+        other => panic!("Expected a date error, got {:?}", other),
+    }
+
+    mock.assert();
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use schoolsoft::{
+    types::error::{RequestError, SchoolListingError},
+    ClientBuilder,
+};
+
+use crate::mock::{login_mock, token_mock};
+
+mod mock;
+
+const SCHOOLS: &str = r#"[{
+    "studentLoginMethods": "4",
+    "parentLoginMethods": "4",
+    "name": "Mock School",
+    "teacherLoginMethods": "4",
+    "url": "https://sms.schoolsoft.se/mock_school/"
+}]"#;
+
+/// Requests that take longer than the timeout fail
+#[tokio::test]
+async fn timeout() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_chunked_body(|body| {
+            std::thread::sleep(Duration::from_millis(500));
+            body.write_all(SCHOOLS.as_bytes())
+        })
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .timeout(Duration::from_millis(100))
+        .build();
+
+    match client.schools().await {
+        Err(SchoolListingError::RequestError(
+            RequestError::RequestError(error) | RequestError::ReadError(error),
+        )) if error.is_timeout() => (),
+        other => panic!("Expected a timeout, got {:?}", other),
+    }
+
+    mock.assert();
+}
+
+/// Requests are sent through the proxy
+#[tokio::test]
+async fn proxy() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url("http://school.invalid".to_string())
+        .proxy(reqwest::Proxy::http(server.url()).unwrap())
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+}
+
+/// The user agent replaces the one from the device profile
+#[tokio::test]
+async fn user_agent() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .match_header("user-agent", "my-tool/1.0")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .user_agent("my-tool/1.0")
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+}
+
+/// A pre-built client is used by the client and the users it creates
+#[tokio::test]
+async fn http_client() {
+    let mut server = mockito::Server::new();
+    let login = login_mock(&mut server, None);
+    let token = token_mock(&mut server, "one_of_those_tokens", None);
+    let lunch = server
+        .mock("GET", "/mock_school/api/lunchmenus/student/1")
+        .match_header("x-prebuilt", "yes")
+        .with_body(include_str!("../hurl/output/lunch.json"))
+        .create();
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-prebuilt", "yes".parse().unwrap());
+    let http_client = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap();
+
+    let mut client = ClientBuilder::new()
+        .base_url(server.url())
+        .http_client(http_client)
+        .build();
+
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+    login.assert();
+
+    let user = client.user.expect("User should be set after login");
+    user.get_lunch().await.expect("Failed to get lunch");
+
+    token.assert();
+    lunch.assert();
+}
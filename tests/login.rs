@@ -1,7 +1,7 @@
 use schoolsoft::{
     types::{
         error::{LoginError, RequestError},
-        UserType,
+        LoginMethod, LoginMethods, SchoolListing, UserType,
     },
     ClientBuilder,
 };
@@ -25,13 +25,13 @@ async fn authorized() {
     assert!(!user.is_of_age);
 
     assert_eq!(user.name, "Mock User");
-    assert_eq!(user.app_key, "123notreal".to_string());
+    assert_eq!(user.app_key().expose(), "123notreal");
     assert_eq!(
         user.pictute_url,
         "pictureFile.jsp?studentId=1337".to_string()
     );
-    assert_eq!(user.app_key, "123notreal".to_string());
-    assert_eq!(user.token, None);
+    assert_eq!(user.app_key().expose(), "123notreal");
+    assert_eq!(user.token(), None);
     assert_eq!(user.user_type, UserType::Student);
     assert_eq!(user.id, 1337);
     assert_eq!(user.orgs.len(), 1);
@@ -66,7 +66,68 @@ async fn failure() {
     mock.assert();
 
     match response {
-        Err(LoginError::RequestError(RequestError::Unauthorized)) => (),
+        Err(LoginError::RequestError(RequestError::Unauthorized(_))) => (),
         _ => panic!("Should return Unauthorized error"),
     }
 }
+
+fn school(student: Vec<LoginMethod>) -> SchoolListing {
+    SchoolListing {
+        login_methods: LoginMethods {
+            student,
+            teacher: vec![],
+            parent: vec![],
+        },
+        name: "Mock School".to_string(),
+        url: "https://sms.schoolsoft.se/mock_school/".to_string(),
+        url_name: "mock_school".to_string(),
+    }
+}
+
+/// Login to a school from the school list that supports app login
+#[test]
+async fn login_to() {
+    let mut server = mockito::Server::new();
+    let mock = login_mock(&mut server, None);
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login_to(
+            &school(vec![LoginMethod::Other(0), LoginMethod::App]),
+            "mock_username",
+            "mock_password",
+            UserType::Student,
+        )
+        .await
+        .expect("Login should be successful");
+
+    assert!(client.user.is_some());
+    mock.assert();
+}
+
+/// No request is made when the school doesn't support app login for the user type
+#[test]
+async fn login_to_unsupported() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .expect(0)
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    let result = client
+        .login_to(
+            &school(vec![LoginMethod::Other(0)]),
+            "mock_username",
+            "mock_password",
+            UserType::Student,
+        )
+        .await;
+
+    match result {
+        Err(LoginError::AppLoginUnsupported(UserType::Student)) => (),
+        other => panic!("Expected AppLoginUnsupported, got {:?}", other),
+    }
+
+    mock.assert();
+}
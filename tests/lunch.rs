@@ -13,7 +13,7 @@ async fn request() {
         Some("mock_school"),
     );
 
-    let mut user = basic_user_with_token(&server.url());
+    let user = basic_user_with_token(&server.url());
 
     let response = user.get_lunch().await;
 
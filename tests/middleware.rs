@@ -0,0 +1,150 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+
+use reqwest::StatusCode;
+use schoolsoft::{
+    http::{Request, Response},
+    middleware::Middleware,
+    retry::RetryPolicy,
+    types::error::{RequestError, SchoolListingError},
+    ClientBuilder,
+};
+
+const SCHOOLS: &str = r#"[{
+    "studentLoginMethods": "4",
+    "parentLoginMethods": "4",
+    "name": "Mock School",
+    "teacherLoginMethods": "4",
+    "url": "https://sms.schoolsoft.se/mock_school/"
+}]"#;
+
+/// Adds a header to every request
+#[derive(Debug)]
+struct AddHeader;
+
+impl Middleware for AddHeader {
+    fn on_request(&self, request: &mut Request) -> Option<Response> {
+        request
+            .headers
+            .push(("x-test".to_string(), "yes".to_string()));
+        None
+    }
+}
+
+/// Answers every request with a status code without sending it
+#[derive(Debug)]
+struct Fail(StatusCode, Arc<AtomicU32>);
+
+impl Middleware for Fail {
+    fn on_request(&self, _request: &mut Request) -> Option<Response> {
+        self.1.fetch_add(1, Ordering::SeqCst);
+
+        Some(Response {
+            status: self.0,
+            headers: Vec::new(),
+            body: String::new(),
+        })
+    }
+}
+
+/// Records the order it is called in
+#[derive(Debug)]
+struct Record(&'static str, Arc<Mutex<Vec<String>>>);
+
+impl Middleware for Record {
+    fn on_request(&self, _request: &mut Request) -> Option<Response> {
+        self.1.lock().unwrap().push(format!("request {}", self.0));
+        None
+    }
+
+    fn on_response(&self, _request: &Request, response: &mut Response) {
+        self.1.lock().unwrap().push(format!("response {}", self.0));
+        response.body = response.body.replace("Mock School", "Changed School");
+    }
+}
+
+/// Headers added by middleware are sent
+#[tokio::test]
+async fn changes_request() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .match_header("x-test", "yes")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .middleware(AddHeader)
+        .build();
+
+    client.schools().await.expect("Failed to get schools");
+    mock.assert();
+}
+
+/// Requests pass through middleware in order and responses in reverse order
+#[tokio::test]
+async fn changes_response() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .middleware(Record("first", calls.clone()))
+        .middleware(Record("second", calls.clone()))
+        .build();
+
+    let schools = client.schools().await.expect("Failed to get schools");
+    mock.assert();
+
+    assert_eq!(schools[0].name, "Changed School");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            "request first",
+            "request second",
+            "response second",
+            "response first"
+        ]
+    );
+}
+
+/// A synthetic response is used instead of sending the request and is retried like a real one
+#[tokio::test]
+async fn short_circuit() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .expect(0)
+        .create();
+
+    let attempts = Arc::new(AtomicU32::new(0));
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(RetryPolicy::new().backoff(Default::default(), Default::default()))
+        .middleware(Fail(StatusCode::SERVICE_UNAVAILABLE, attempts.clone()))
+        .build();
+
+    match client.schools().await {
+        Err(SchoolListingError::RequestError(RequestError::UncheckedCode(
+            StatusCode::SERVICE_UNAVAILABLE,
+            _,
+        ))) => (),
+        other => panic!("Expected UncheckedCode(503), got {:?}", other),
+    }
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    mock.assert();
+}
@@ -1,12 +1,20 @@
 #![allow(dead_code, deprecated)]
 
+use std::sync::Arc;
+
 use chrono::NaiveDate;
-use mockito::{Mock, ServerGuard};
+use mockito::{Matcher, Mock, ServerGuard};
 use reqwest::Url;
-use schoolsoft::user::{Org, Token, User, UserType};
+use schoolsoft::{
+    clock::FixedClock,
+    user::{Org, Token, User, UserType},
+};
 
 pub fn basic_user(url: &str) -> User {
-    let url = Url::parse(url).expect("url should be parsable").join("/mock_school").expect("should be able to join with mock_school");
+    let url = Url::parse(url)
+        .expect("url should be parsable")
+        .join("/mock_school")
+        .expect("should be able to join with mock_school");
     User::new(
         url.clone(),
         "mock_user".to_string(),
@@ -25,21 +33,27 @@ pub fn basic_user(url: &str) -> User {
     )
 }
 
-/// Create a basic user with a token and a modified function for getting the current time so it
-/// thinks that the token is not expired
+/// Clock stopped at a time where tokens from [`token_mock`] are still valid
+pub fn mock_clock() -> Arc<FixedClock> {
+    Arc::new(FixedClock::new(
+        NaiveDate::from_ymd(2024, 2, 12).and_hms(16, 22, 23),
+    ))
+}
+
+/// Create a basic user with a token and a fixed clock so it thinks that the token is not expired
 pub fn basic_user_with_token(url: &str) -> User {
-    let mut user = basic_user(url);
+    let user = basic_user(url).with_clock(mock_clock());
 
-    let token = Token {
-        token: "one_of_those_tokens".to_string(),
-        now: || NaiveDate::from_ymd(2024, 2, 12).and_hms(19, 22, 23),
-        expires: NaiveDate::from_ymd(2024, 2, 12).and_hms(20, 22, 23),
-    };
+    let token = Token::new_with_clock(
+        "one_of_those_tokens".to_string(),
+        NaiveDate::from_ymd(2024, 2, 12).and_hms(17, 22, 23),
+        user.clock.clone(),
+    );
 
     dbg!(token.expires_in());
     assert!(token.is_safe());
 
-    user.token = Some(token);
+    user.set_token(Some(token));
 
     user
 }
@@ -63,34 +77,47 @@ pub fn post(server: &mut ServerGuard, path: &str, body: &str, school: Option<&st
 }
 
 pub fn login_mock(server: &mut ServerGuard, school: Option<&str>) -> Mock {
-    let url = server.url();
+    login_mock_as(server, school, UserType::Student)
+}
+
+/// Mock a login that only matches when logging in as `user_type`
+pub fn login_mock_as(server: &mut ServerGuard, school: Option<&str>, user_type: UserType) -> Mock {
     let school = school.unwrap_or("mock_school");
-    post(
-        server,
-        "rest/app/login",
-        format!(
-            r#"{{
-                "pictureUrl": "pictureFile.jsp?studentId=1337",
-                "name": "Mock User",
-                "isOfAge": false,
-                "appKey": "123notreal",
-                "orgs": [
-                    {{
-                        "name": "Mock School",
-                        "blogger": false,
-                        "schoolType": 9,
-                        "leisureSchool": 0,
-                        "class": "F35b",
-                        "orgId": 1,
-                        "tokenLogin": "{url}/{school}/jsp/app/TokenLogin.jsp?token=TOKEN_PLACEHOLDER&orgid=1&childid=1337&redirect=https%3A%2F%2Fsms1.schoolsoft.se%2mock_school%2Fjsp%2Fstudent%2Fright_student_startpage.jsp"
-                    }}
-                ],
-                "type": 1,
-                "userId": 1337
-            }}"#,
-        )
-        .as_str(),
-        Some(school),
+    let body = login_body(&server.url(), school, user_type);
+    server
+        .mock("POST", format!("/{school}/rest/app/login").as_str())
+        .match_body(Matcher::UrlEncoded(
+            "usertype".into(),
+            (user_type as u8).to_string(),
+        ))
+        .with_status(200)
+        .with_body(body)
+        .create()
+}
+
+/// Response to a successful login at `school` on the server at `url`
+pub fn login_body(url: &str, school: &str, user_type: UserType) -> String {
+    let user_type = user_type as u8;
+    format!(
+        r#"{{
+            "pictureUrl": "pictureFile.jsp?studentId=1337",
+            "name": "Mock User",
+            "isOfAge": false,
+            "appKey": "123notreal",
+            "orgs": [
+                {{
+                    "name": "Mock School",
+                    "blogger": false,
+                    "schoolType": 9,
+                    "leisureSchool": 0,
+                    "class": "F35b",
+                    "orgId": 1,
+                    "tokenLogin": "{url}/{school}/jsp/app/TokenLogin.jsp?token=TOKEN_PLACEHOLDER&orgid=1&childid=1337&redirect=https%3A%2F%2Fsms1.schoolsoft.se%2mock_school%2Fjsp%2Fstudent%2Fright_student_startpage.jsp"
+                }}
+            ],
+            "type": {user_type},
+            "userId": 1337
+        }}"#,
     )
 }
 
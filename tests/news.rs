@@ -0,0 +1,128 @@
+#![cfg(feature = "unverified")]
+
+use chrono::NaiveDate;
+use schoolsoft::types::{
+    error::{NewsError, NewsParseError},
+    Attachment,
+};
+
+use crate::mock::{basic_user_with_token, get};
+
+mod mock;
+
+const NEWS: &str = r#"[
+    {
+        "id": 4711,
+        "title": "Sportlov",
+        "text": "Skolan är stängd vecka 9.",
+        "publisher": "Mock Teacher",
+        "fromDate": "2024-02-16 15:03:15.0",
+        "toDate": "2024-03-04",
+        "read": true,
+        "attachments": [
+            {
+                "id": 12,
+                "name": "schema.pdf",
+                "size": 48213,
+                "contentType": "application/pdf"
+            },
+            {
+                "id": 13,
+                "name": "karta"
+            }
+        ]
+    },
+    {
+        "id": 4712,
+        "title": "Utvecklingssamtal",
+        "fromDate": "2024-02-12",
+        "toDate": ""
+    }
+]"#;
+
+/// Publishing dates can be missing the time and news without an end date has an empty one
+#[tokio::test]
+async fn dates() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(&mut server, "api/news/student/1", NEWS, Some("mock_school"));
+
+    let user = basic_user_with_token(&server.url());
+
+    let news = user.get_news().await.expect("Failed to get news");
+
+    mock.assert();
+
+    let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+    assert_eq!(
+        news[0].published,
+        date(2, 16).and_hms_opt(15, 3, 15).unwrap()
+    );
+    assert_eq!(news[0].expires, date(3, 4).and_hms_opt(0, 0, 0));
+    assert_eq!(news[1].published, date(2, 12).and_hms_opt(0, 0, 0).unwrap());
+    assert_eq!(news[1].expires, None);
+}
+
+/// `text` and `publisher` become the body and author, and are empty when missing
+#[tokio::test]
+async fn fields() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(&mut server, "api/news/student/1", NEWS, Some("mock_school"));
+
+    let user = basic_user_with_token(&server.url());
+
+    let news = user.get_news().await.expect("Failed to get news");
+
+    mock.assert();
+
+    assert_eq!(news[0].body, "Skolan är stängd vecka 9.");
+    assert_eq!(news[0].author, "Mock Teacher");
+    assert!(news[0].read);
+    assert_eq!(
+        news[0].attachments,
+        vec![
+            Attachment {
+                id: 12,
+                name: "schema.pdf".to_string(),
+                size: Some(48213),
+                content_type: Some("application/pdf".to_string()),
+            },
+            Attachment {
+                id: 13,
+                name: "karta".to_string(),
+                size: None,
+                content_type: None,
+            },
+        ]
+    );
+
+    assert_eq!(news[1].body, "");
+    assert_eq!(news[1].author, "");
+    assert!(!news[1].read);
+    assert!(news[1].attachments.is_empty());
+}
+
+/// The date that couldn't be parsed is kept in the error
+#[tokio::test]
+async fn bad_date() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/news/student/1",
+        r#"[{"id": 1, "title": "Sportlov", "fromDate": "16/2 2024"}]"#,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    match user.get_news().await {
+        Err(NewsError::ParseError(NewsParseError::DateParseError(date, _))) => {
+            assert_eq!(date, "16/2 2024")
+        }
+        other => panic!("Expected a date error, got {:?}", other),
+    }
+
+    mock.assert();
+}
@@ -0,0 +1,257 @@
+use schoolsoft::{
+    deserializers::Deserializer,
+    ClientBuilder,
+    types::{
+        error::{LunchMenuError, RequestError, ScheduleError},
+        User,
+    },
+    user::Org,
+};
+
+use crate::mock::{basic_user_with_token, get};
+
+mod mock;
+
+/// A user that is part of the mock school and its leisure-time centre
+fn two_org_user(url: &str) -> User {
+    let mut user = basic_user_with_token(url);
+
+    user.orgs.push(Org {
+        id: 2,
+        name: "Mock Leisure Centre".to_string(),
+        ..user.orgs[0].clone()
+    });
+
+    user
+}
+
+/// A single occasion on mondays in week 11, belonging to org 2
+const LEISURE_SCHEDULE: &str = r#"[{
+    "weeks": 0,
+    "excludingWeeks": 0,
+    "creById": 0,
+    "source": {},
+    "externalRef": "",
+    "subjectId": 1,
+    "orgId": 2,
+    "updDate": "2023-08-16 12:42:41.0",
+    "updByType": -1,
+    "excludeClass": 0,
+    "startTime": "1970-01-01 07:00:00.0",
+    "id": 1,
+    "includingWeeks": 0,
+    "subjectName": "Fritids",
+    "updById": 0,
+    "creByType": -1,
+    "creDate": "2023-08-16 12:42:41.0",
+    "length": 60,
+    "externalId": "",
+    "roomName": "Fritids",
+    "periodWeeks": 0,
+    "includingWeeksString": "",
+    "dayId": 0,
+    "name": "",
+    "absenceType": 1,
+    "guid": "afbae58f-c35e-4480-bfd1-574fc8de5573",
+    "excludingWeeksString": "",
+    "endTime": "1970-01-01 08:00:00.0",
+    "weeksString": "11",
+    "tmpLesson": 0
+}]"#;
+
+/// Requests are made for the first org unless another one is selected
+#[tokio::test]
+async fn select_org() {
+    let mut server = mockito::Server::new();
+    let body = include_str!("../hurl/output/lunch.json");
+
+    let first = get(&mut server, "api/lunchmenus/student/1", body, None);
+    let second = get(&mut server, "api/lunchmenus/student/2", body, None);
+
+    let mut user = two_org_user(&server.url());
+    assert_eq!(user.org().unwrap().id, 1);
+    user.get_lunch().await.expect("Getting lunch should work");
+
+    user.select_org(2).expect("User is part of org 2");
+    assert_eq!(user.org().unwrap().id, 2);
+    user.get_lunch().await.expect("Getting lunch should work");
+
+    // Per org variants ignore the active org
+    user.get_lunch_for(&user.orgs[0])
+        .await
+        .expect("Getting lunch should work");
+
+    first.expect(2).assert();
+    second.expect(1).assert();
+}
+
+/// Selecting an org the user isn't part of fails and leaves the active org alone
+#[test]
+fn select_unknown_org() {
+    let mut user = two_org_user("http://localhost");
+
+    match user.select_org(3) {
+        Err(RequestError::UnknownOrganization(3)) => (),
+        other => panic!("Expected UnknownOrganization, got {:?}", other),
+    }
+
+    assert_eq!(user.org().unwrap().id, 1);
+}
+
+/// A user without any orgs gets an error instead of a panic
+#[tokio::test]
+async fn no_orgs() {
+    let server = mockito::Server::new();
+
+    let mut user = basic_user_with_token(&server.url());
+    user.orgs.clear();
+
+    match user.get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::NoOrganization)) => (),
+        other => panic!("Expected NoOrganization, got {:?}", other),
+    }
+
+    match user.get_all_schedules().await {
+        Err(ScheduleError::RequestError(RequestError::NoOrganization)) => (),
+        other => panic!("Expected NoOrganization, got {:?}", other),
+    }
+}
+
+/// Without a school url, login responses without any orgs can't be turned into a user
+#[test]
+fn deserialize_without_orgs() {
+    let result = User::deserialize(
+        r#"{
+            "pictureUrl": "pictureFile.jsp?studentId=1337",
+            "name": "Mock User",
+            "isOfAge": false,
+            "appKey": "123notreal",
+            "orgs": [],
+            "type": 1,
+            "userId": 1337
+        }"#,
+    );
+
+    assert!(result.is_err());
+}
+
+/// Users without any orgs can login, requests for data fail with NoOrganization
+#[tokio::test]
+async fn login_without_orgs() {
+    let mut server = mockito::Server::new();
+
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .with_status(200)
+        .with_body(
+            r#"{
+                "pictureUrl": "pictureFile.jsp?studentId=1337",
+                "name": "Mock User",
+                "isOfAge": false,
+                "appKey": "123notreal",
+                "orgs": [],
+                "type": 1,
+                "userId": 1337
+            }"#,
+        )
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    login.assert();
+
+    let user = client.user.expect("User should be set after login");
+    assert_eq!(user.school_url.as_str(), format!("{}/mock_school", server.url()));
+    assert!(user.org().is_none());
+
+    match user.get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::NoOrganization)) => (),
+        other => panic!("Expected NoOrganization, got {:?}", other),
+    }
+}
+
+/// The school url comes from the token login of the first org, not from where the login was sent
+#[tokio::test]
+async fn login_uses_token_login_host() {
+    let mut server = mockito::Server::new();
+    let body = r#"{
+        "pictureUrl": "pictureFile.jsp?studentId=1337",
+        "name": "Mock User",
+        "isOfAge": false,
+        "appKey": "123notreal",
+        "orgs": [{
+            "name": "Mock School",
+            "blogger": false,
+            "schoolType": 9,
+            "leisureSchool": 0,
+            "class": "F35b",
+            "orgId": 1,
+            "tokenLogin": "https://sms1.schoolsoft.se/mock_school/jsp/app/TokenLogin.jsp?token=TOKEN_PLACEHOLDER&orgid=1"
+        }],
+        "type": 1,
+        "userId": 1337
+    }"#;
+
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .with_status(200)
+        .with_body(body)
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    login.assert();
+
+    let user = client.user.expect("User should be set after login");
+    let parsed = User::deserialize(body).expect("Failed to deserialize JSON");
+    assert_eq!(user.school_url.as_str(), "https://sms1.schoolsoft.se/mock_school");
+    assert_eq!(user.school_url, parsed.school_url);
+}
+
+/// Schedules from all orgs are merged with every lesson tagged by its org
+#[tokio::test]
+async fn all_schedules() {
+    let mut server = mockito::Server::new();
+
+    let school = get(
+        &mut server,
+        "api/lessons/student/1",
+        include_str!("../hurl/output/schedule.json"),
+        None,
+    );
+    let leisure = get(&mut server, "api/lessons/student/2", LEISURE_SCHEDULE, None);
+
+    let user = two_org_user(&server.url());
+
+    let schedule = user
+        .get_all_schedules()
+        .await
+        .expect("Getting all schedules should work");
+
+    let lessons = &schedule.weeks[10].monday.lessons;
+    assert!(lessons.iter().any(|lesson| lesson.org_id == 1));
+
+    // The leisure lesson starts first so it is sorted first
+    assert_eq!(lessons[0].name, "Fritids");
+    assert_eq!(lessons[0].org_id, 2);
+
+    let total: usize = schedule
+        .weeks
+        .iter()
+        .map(|week| week.day(chrono::Weekday::Mon))
+        .flat_map(|day| &day.lessons)
+        .filter(|lesson| lesson.org_id == 2)
+        .count();
+    assert_eq!(total, 1);
+
+    school.assert();
+    leisure.assert();
+}
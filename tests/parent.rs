@@ -0,0 +1,131 @@
+use schoolsoft::types::{
+    error::{LunchMenuError, RequestError},
+    UserType,
+};
+#[cfg(feature = "unverified")]
+use schoolsoft::{
+    types::{error::ChildrenError, Child},
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user, token_mock};
+#[cfg(feature = "unverified")]
+use crate::mock::login_mock_as;
+
+mod mock;
+
+#[cfg(feature = "unverified")]
+const CHILDREN: &str = r#"[
+    {
+        "userId": 1338,
+        "name": "Mock Child",
+        "class": "F35b",
+        "orgId": 1
+    },
+    {
+        "userId": 1339,
+        "name": "Other Child",
+        "class": "F32a",
+        "orgId": 1
+    }
+]"#;
+
+/// Login as a parent, list the children and get lunch for one of them
+#[cfg(feature = "unverified")]
+#[tokio::test]
+async fn parent_login() {
+    let mut server = mockito::Server::new();
+    let login = login_mock_as(&mut server, None, UserType::Parent);
+    let token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+
+    let children = server
+        .mock("GET", "/mock_school/api/students/parent/1")
+        .with_body(CHILDREN)
+        .create();
+
+    let lunch = server
+        .mock("GET", "/mock_school/api/lunchmenus/parent/1/1339")
+        .with_body(include_str!("../hurl/output/lunch.json"))
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login_as("mock_username", "mock_password", "mock_school", UserType::Parent)
+        .await
+        .expect("Login should be successful");
+
+    let user = client.user.as_mut().unwrap();
+    assert_eq!(user.user_type, UserType::Parent);
+
+    let list = user.get_children().await.expect("Getting children should work");
+    assert_eq!(
+        list,
+        vec![
+            Child {
+                id: 1338,
+                name: "Mock Child".to_string(),
+                class: "F35b".to_string(),
+                org_id: 1,
+            },
+            Child {
+                id: 1339,
+                name: "Other Child".to_string(),
+                class: "F32a".to_string(),
+                org_id: 1,
+            },
+        ]
+    );
+
+    user.select_child(list[1].clone());
+    user.get_lunch().await.expect("Getting lunch should work");
+
+    login.assert();
+    token.assert();
+    children.assert();
+    lunch.assert();
+}
+
+/// Parents have to select a child before getting anything child specific
+#[cfg(feature = "unverified")]
+#[tokio::test]
+async fn no_child_selected() {
+    let mut server = mockito::Server::new();
+    let _token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+
+    let mut user = basic_user(&server.url());
+    user.user_type = UserType::Parent;
+
+    match user.get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::NoChildSelected)) => (),
+        other => panic!("Expected NoChildSelected, got {:?}", other),
+    }
+}
+
+/// Only parents have children
+#[cfg(feature = "unverified")]
+#[tokio::test]
+async fn student_has_no_children() {
+    let server = mockito::Server::new();
+    let user = basic_user(&server.url());
+
+    match user.get_children().await {
+        Err(ChildrenError::NotAParent) => (),
+        other => panic!("Expected NotAParent, got {:?}", other),
+    }
+}
+
+/// Without the `unverified` feature a child can't be selected, so parents can't get anything
+#[cfg(not(feature = "unverified"))]
+#[tokio::test]
+async fn parents_unsupported() {
+    let mut server = mockito::Server::new();
+    let _token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+
+    let mut user = basic_user(&server.url());
+    user.user_type = UserType::Parent;
+
+    match user.get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::ParentsUnsupported)) => (),
+        other => panic!("Expected ParentsUnsupported, got {:?}", other),
+    }
+}
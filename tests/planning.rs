@@ -0,0 +1,141 @@
+#![cfg(feature = "unverified")]
+
+use chrono::NaiveDate;
+use schoolsoft::types::{
+    error::{PlanningError, PlanningParseError},
+    PlanningKind,
+};
+
+use crate::mock::{basic_user_with_token, get};
+
+mod mock;
+
+const PLANNING: &str = r#"[
+    {
+        "id": 501,
+        "type": "assignment",
+        "title": "Labbrapport",
+        "description": "Lämna in via SchoolSoft",
+        "subjectName": "Kemi 1",
+        "dueDate": "2024-02-16 23:59:00.0",
+        "lessonId": 0,
+        "attachments": [
+            {
+                "id": 7,
+                "name": "mall.docx",
+                "contentType": "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+        ]
+    },
+    {
+        "id": 502,
+        "type": "test",
+        "title": "Prov kapitel 3",
+        "subjectName": "Matematik 1c",
+        "dueDate": "2024-02-19 08:15:00.0",
+        "lessonId": 1234
+    },
+    {
+        "id": 503,
+        "type": "deadline",
+        "title": "Sista dag för komplettering",
+        "subjectName": "Svenska 1",
+        "dueDate": "2024-02-23 16:00:00.0"
+    },
+    {
+        "id": 504,
+        "type": "excursion",
+        "title": "Studiebesök",
+        "subjectName": "Biologi 1",
+        "dueDate": "2024-02-26 09:00:00.0"
+    }
+]"#;
+
+/// Unknown types become [`PlanningKind::Other`] instead of failing the whole list
+#[tokio::test]
+async fn kinds() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/planning/student/1",
+        PLANNING,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let planning = user.get_planning().await.expect("Failed to get planning");
+
+    mock.assert();
+
+    let kinds: Vec<_> = planning.iter().map(|item| item.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            PlanningKind::Assignment,
+            PlanningKind::Test,
+            PlanningKind::Deadline,
+            PlanningKind::Other
+        ]
+    );
+}
+
+/// `dueDate` includes the time and a lesson id of 0, or none at all, means no lesson
+#[tokio::test]
+async fn due_and_lesson() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/planning/student/1",
+        PLANNING,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    let planning = user.get_planning().await.expect("Failed to get planning");
+
+    mock.assert();
+
+    assert_eq!(
+        planning[1].due,
+        NaiveDate::from_ymd_opt(2024, 2, 19)
+            .unwrap()
+            .and_hms_opt(8, 15, 0)
+            .unwrap()
+    );
+    assert_eq!(planning[0].occasion_id, None);
+    assert_eq!(planning[1].occasion_id, Some(1234));
+    assert_eq!(planning[2].occasion_id, None);
+
+    assert_eq!(planning[0].course, "Kemi 1");
+    assert_eq!(planning[1].description, "");
+    assert_eq!(planning[0].attachments[0].size, None);
+    assert!(planning[1].attachments.is_empty());
+}
+
+/// Due dates without a time aren't accepted
+#[tokio::test]
+async fn bad_date() {
+    let mut server = mockito::Server::new();
+
+    let mock = get(
+        &mut server,
+        "api/planning/student/1",
+        r#"[{"id": 1, "type": "test", "title": "", "subjectName": "", "dueDate": "2024-02-19"}]"#,
+        Some("mock_school"),
+    );
+
+    let user = basic_user_with_token(&server.url());
+
+    match user.get_planning().await {
+        Err(PlanningError::ParseError(PlanningParseError::DateParseError(date, _))) => {
+            assert_eq!(date, "2024-02-19")
+        }
+        other => panic!("Expected a date error, got {:?}", other),
+    }
+
+    mock.assert();
+}
@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use schoolsoft::{
+    rate_limit::{RateLimit, RateLimiter},
+    types::error::{RequestError, TokenError},
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user, login_mock, token_mock};
+
+mod mock;
+
+/// Requests wait for room instead of failing by default
+#[tokio::test]
+async fn waits_for_capacity() {
+    let mut server = mockito::Server::new();
+    let schools = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body("[]")
+        .expect(3)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .rate_limit(RateLimiter::new().limit(RateLimit::new(1, Duration::from_millis(200))))
+        .build();
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        client.schools().await.expect("Getting schools should work");
+    }
+    assert!(start.elapsed() >= Duration::from_millis(400));
+
+    schools.assert();
+}
+
+/// The limit of a school is shared between the client and the users it creates
+#[tokio::test]
+async fn shared_with_users() {
+    let mut server = mockito::Server::new();
+    let login = login_mock(&mut server, None);
+    let token = token_mock(&mut server, "one_of_those_tokens", None);
+
+    let mut client = ClientBuilder::new()
+        .base_url(server.url())
+        .rate_limit(
+            RateLimiter::new()
+                .school("mock_school", RateLimit::new(2, Duration::from_secs(60)))
+                .wait(false),
+        )
+        .build();
+
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    let user = client.user.as_ref().unwrap();
+    user.get_token().await.expect("Second request should fit");
+
+    // A user created manually shares the limit once adopted by the client
+    let other = client.adopt(basic_user(&server.url()));
+
+    match other.get_token().await {
+        Err(TokenError::RequestError(RequestError::RateLimited(wait))) => {
+            assert!(wait > Duration::from_secs(1))
+        }
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+
+    // Requests that don't belong to the school are not limited
+    let schools = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body("[]")
+        .create();
+    client.schools().await.expect("Getting schools should work");
+
+    login.assert();
+    token.expect(1).assert();
+    schools.assert();
+}
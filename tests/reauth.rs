@@ -0,0 +1,102 @@
+use schoolsoft::{
+    credentials::Credentials,
+    types::error::{LunchMenuError, RequestError, TokenError},
+};
+
+use crate::mock::{basic_user, login_mock};
+
+mod mock;
+
+/// A revoked app key should be replaced by logging in again, then the call is retried
+#[tokio::test]
+async fn revoked_app_key() {
+    let mut server = mockito::Server::new();
+
+    let rejected = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .match_header("appKey", "revoked")
+        .with_status(401)
+        .create();
+
+    let login = login_mock(&mut server, None);
+
+    let accepted = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .match_header("appKey", "123notreal")
+        .with_body(r#"{"expiryDate":"2099-01-01 00:00:00.0","token":"one_of_those_tokens"}"#)
+        .create();
+
+    let lunch = server
+        .mock("GET", "/mock_school/api/lunchmenus/student/1")
+        .match_header("token", "one_of_those_tokens")
+        .with_body(include_str!("../hurl/output/lunch.json"))
+        .create();
+
+    let user = basic_user(&server.url())
+        .with_credentials(Credentials::new("mock_username", "mock_password"));
+    user.set_app_key("revoked");
+
+    user.get_lunch().await.expect("Getting lunch should work");
+
+    assert_eq!(user.app_key().expose(), "123notreal");
+
+    rejected.assert();
+    login.assert();
+    accepted.assert();
+    lunch.assert();
+}
+
+/// Without credentials the error is returned like before
+#[tokio::test]
+async fn no_credentials() {
+    let mut server = mockito::Server::new();
+
+    let rejected = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .with_status(401)
+        .create();
+
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .expect(0)
+        .create();
+
+    let user = basic_user(&server.url());
+
+    match user.get_lunch().await {
+        Err(LunchMenuError::TokenError(TokenError::RequestError(RequestError::Unauthorized(_)))) => (),
+        other => panic!("Expected Unauthorized, got {:?}", other),
+    }
+
+    rejected.assert();
+    login.assert();
+}
+
+/// If logging in again also fails, that error is returned instead of retrying forever
+#[tokio::test]
+async fn bad_credentials() {
+    let mut server = mockito::Server::new();
+
+    let rejected = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .with_status(401)
+        .expect(1)
+        .create();
+
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .with_status(401)
+        .expect(1)
+        .create();
+
+    let user =
+        basic_user(&server.url()).with_credentials(Credentials::new("mock_username", "wrong"));
+
+    match user.smart_token().await {
+        Err(TokenError::LoginError(_)) => (),
+        other => panic!("Expected LoginError, got {:?}", other),
+    }
+
+    rejected.assert();
+    login.assert();
+}
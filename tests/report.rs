@@ -0,0 +1,238 @@
+#![cfg(feature = "unverified")]
+
+use chrono::{NaiveDate, NaiveTime};
+use mockito::Matcher;
+use reqwest::Method;
+use schoolsoft::{
+    cache::Cache,
+    clock::Clock,
+    report::{AbsenceReport, ReportOutcome},
+    retry::RetryPolicy,
+    schedule::{Lesson, Schedule},
+    secret::Secret,
+    types::error::{ReportError, ReportValidationError, RequestError},
+    user::User,
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user_with_token, get, mock_clock, post};
+
+mod mock;
+
+/// The day after [`mock_clock`]
+fn tuesday() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 2, 13).unwrap()
+}
+
+/// Schedule with a single lesson on [`tuesday`]
+fn schedule() -> Schedule {
+    let mut schedule = Schedule::from(mock_clock().today());
+    schedule.weeks[6].tuesday.lessons.push(Lesson {
+        start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        name: "Math".to_string(),
+        room: "A1".to_string(),
+        org_id: 1,
+        occasion_id: 1234,
+    });
+    schedule
+}
+
+fn adult_student(url: &str) -> User {
+    let mut user = basic_user_with_token(url);
+    user.is_of_age = true;
+    user
+}
+
+/// Dry runs build the request without sending anything
+#[tokio::test]
+async fn dry_run() {
+    let user = adult_student("http://localhost");
+    let report = AbsenceReport::lessons([(tuesday(), 1234)])
+        .reason("Sick")
+        .dry_run(true);
+
+    let request = match user.report_absence(&report, &schedule()).await {
+        Ok(ReportOutcome::DryRun(request)) => request,
+        other => panic!("Expected a dry run, got {:?}", other),
+    };
+
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(
+        request.url,
+        "http://localhost/mock_school/api/absences/student/1"
+    );
+    assert_eq!(
+        request.body.as_ref().map(Secret::expose),
+        Some("lesson=2024-02-13%3A1234&reason=Sick")
+    );
+    assert_eq!(request.header_value("token"), None);
+}
+
+#[tokio::test]
+async fn accepted() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("POST", "/mock_school/api/absences/student/1")
+        .match_header("token", "one_of_those_tokens")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("fromDate".into(), "2024-02-13".into()),
+            Matcher::UrlEncoded("toDate".into(), "2024-02-13".into()),
+        ]))
+        .with_body(r#"{"success": true, "absenceIds": [9001]}"#)
+        .create();
+
+    let user = adult_student(&server.url());
+    let report = AbsenceReport::days(tuesday(), tuesday());
+
+    let outcome = user
+        .report_absence(&report, &schedule())
+        .await
+        .expect("Failed to report absence");
+
+    mock.assert();
+    assert_eq!(outcome, ReportOutcome::Accepted(vec![9001]));
+}
+
+/// Cached absences are downloaded again after a report is accepted
+#[tokio::test]
+async fn accepted_invalidates_absences() {
+    let mut server = mockito::Server::new();
+
+    let absences = get(&mut server, "api/absences/student/1", "[]", None).expect(2);
+    let report = post(
+        &mut server,
+        "api/absences/student/1",
+        r#"{"success": true, "absenceIds": [9001]}"#,
+        None,
+    );
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .cache(Cache::memory())
+        .clock(mock_clock())
+        .build();
+    let user = client.adopt(adult_student(&server.url()));
+
+    user.get_absences().await.expect("Failed to get absences");
+    user.get_absences()
+        .await
+        .expect("Failed to get cached absences");
+
+    user.report_absence(&AbsenceReport::days(tuesday(), tuesday()), &schedule())
+        .await
+        .expect("Failed to report absence");
+    user.get_absences().await.expect("Failed to get absences");
+
+    absences.assert();
+    report.assert();
+}
+
+#[tokio::test]
+async fn rejected() {
+    let mut server = mockito::Server::new();
+
+    let mock = post(
+        &mut server,
+        "api/absences/student/1",
+        r#"{"success": false, "message": "Absence has already been reported"}"#,
+        None,
+    );
+
+    let user = adult_student(&server.url());
+    let report = AbsenceReport::days(tuesday(), tuesday());
+
+    let outcome = user
+        .report_absence(&report, &schedule())
+        .await
+        .expect("Failed to report absence");
+
+    mock.assert();
+    assert_eq!(
+        outcome,
+        ReportOutcome::Rejected("Absence has already been reported".to_string())
+    );
+}
+
+/// Reports refused with a client error status are rejected, not errors
+#[tokio::test]
+async fn rejected_status() {
+    let mut server = mockito::Server::new();
+
+    let conflict = server
+        .mock("POST", "/mock_school/api/absences/student/1")
+        .with_status(409)
+        .with_body(r#"{"success": false, "message": "Absence has already been reported"}"#)
+        .create();
+
+    let user = adult_student(&server.url());
+    let report = AbsenceReport::days(tuesday(), tuesday());
+
+    let outcome = user
+        .report_absence(&report, &schedule())
+        .await
+        .expect("Failed to report absence");
+
+    conflict.assert();
+    assert_eq!(
+        outcome,
+        ReportOutcome::Rejected("Absence has already been reported".to_string())
+    );
+    conflict.remove();
+
+    let forbidden = server
+        .mock("POST", "/mock_school/api/absences/student/1")
+        .with_status(403)
+        .with_body("Not allowed")
+        .create();
+
+    let outcome = user
+        .report_absence(&report, &schedule())
+        .await
+        .expect("Failed to report absence");
+
+    forbidden.assert();
+    assert_eq!(outcome, ReportOutcome::Rejected("Not allowed".to_string()));
+}
+
+/// Invalid reports are never sent
+#[tokio::test]
+async fn invalid() {
+    let user = basic_user_with_token("http://localhost");
+    let report = AbsenceReport::days(tuesday(), tuesday());
+
+    match user.report_absence(&report, &schedule()).await {
+        Err(ReportError::InvalidReport(ReportValidationError::NotAllowed)) => (),
+        other => panic!("Expected the report to be refused, got {:?}", other),
+    }
+}
+
+/// Reports are not retried, even if the client retries other requests
+#[tokio::test]
+async fn not_retried() {
+    let mut server = mockito::Server::new();
+
+    let mock = server
+        .mock("POST", "/mock_school/api/absences/student/1")
+        .with_status(500)
+        .expect(1)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(RetryPolicy::new().jitter(false))
+        .clock(mock_clock())
+        .build();
+    let user = client.adopt(adult_student(&server.url()));
+
+    match user
+        .report_absence(&AbsenceReport::days(tuesday(), tuesday()), &schedule())
+        .await
+    {
+        Err(ReportError::RequestError(RequestError::InternalServerError(_))) => (),
+        other => panic!("Expected a server error, got {:?}", other),
+    }
+
+    mock.assert();
+}
@@ -0,0 +1,234 @@
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use schoolsoft::{
+    clock::FixedClock,
+    retry::RetryPolicy,
+    types::error::{LunchMenuError, RequestError, SchoolListingError},
+    ClientBuilder,
+};
+
+use crate::mock::{login_mock, token_mock};
+
+mod mock;
+
+const SCHOOLS: &str = r#"[{
+    "studentLoginMethods": "4",
+    "parentLoginMethods": "4",
+    "name": "Mock School",
+    "teacherLoginMethods": "4",
+    "url": "https://sms.schoolsoft.se/mock_school/"
+}]"#;
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy::new()
+        .max_attempts(3)
+        .backoff(Duration::from_millis(1), Duration::from_millis(1))
+}
+
+/// A request that fails with a server error is sent again
+#[tokio::test]
+async fn retries_server_error() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(500)
+        .expect(3)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries())
+        .build();
+
+    match client.schools().await {
+        Err(SchoolListingError::RequestError(RequestError::InternalServerError(_))) => (),
+        other => panic!("Expected InternalServerError, got {:?}", other),
+    }
+    failing.assert();
+
+    // Succeeds on the last attempt
+    failing.remove();
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(502)
+        .expect(2)
+        .create();
+    let working = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries().max_attempts(5))
+        .build();
+
+    client.schools().await.expect("Third attempt should work");
+    failing.assert();
+    working.assert();
+}
+
+/// Without a retry policy every request is only sent once
+#[tokio::test]
+async fn no_retries_by_default() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(500)
+        .expect(1)
+        .create();
+
+    let client = ClientBuilder::new().base_url(server.url()).build();
+
+    assert!(client.schools().await.is_err());
+    failing.assert();
+}
+
+/// The retry policy set on the client is used by the users it creates
+#[tokio::test]
+async fn user_inherits_policy() {
+    let mut server = mockito::Server::new();
+    let login = login_mock(&mut server, None);
+    let token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+
+    let lunch = server
+        .mock("GET", "/mock_school/api/lunchmenus/student/1")
+        .with_status(503)
+        .expect(3)
+        .create();
+
+    let mut client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries())
+        .build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    match client.user.unwrap().get_lunch().await {
+        Err(LunchMenuError::RequestError(RequestError::UncheckedCode(code, _))) => {
+            assert_eq!(code, 503)
+        }
+        other => panic!("Expected 503, got {:?}", other),
+    }
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+}
+
+/// Unauthorized is not retried as sending the same request again won't help
+#[tokio::test]
+async fn unauthorized_not_retried() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(401)
+        .expect(1)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries())
+        .build();
+
+    assert!(client.schools().await.is_err());
+    failing.assert();
+}
+
+/// The wait from the Retry-After header is used instead of the backoff
+#[tokio::test]
+async fn honours_retry_after() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(429)
+        .with_header("Retry-After", "1")
+        .expect(1)
+        .create();
+    let working = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries())
+        .build();
+
+    let start = Instant::now();
+    client.schools().await.expect("Second attempt should work");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    failing.assert();
+    working.assert();
+}
+
+/// Retry-After dates are compared with the client's clock
+#[tokio::test]
+async fn retry_after_date_uses_clock() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(503)
+        .with_header("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")
+        .expect(1)
+        .create();
+    let working = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let now = NaiveDate::from_ymd_opt(2015, 10, 21)
+        .unwrap()
+        .and_hms_opt(7, 27, 59)
+        .unwrap();
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries())
+        .clock(FixedClock::new(now))
+        .build();
+
+    let start = Instant::now();
+    client.schools().await.expect("Second attempt should work");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    failing.assert();
+    working.assert();
+}
+
+/// Long Retry-After waits are shortened to the policy's maximum
+#[tokio::test]
+async fn retry_after_capped() {
+    let mut server = mockito::Server::new();
+
+    let failing = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_status(429)
+        .with_header("Retry-After", "86400")
+        .expect(1)
+        .create();
+    let working = server
+        .mock("GET", "/rest/app/schoollist/prod")
+        .with_body(SCHOOLS)
+        .create();
+
+    let client = ClientBuilder::new()
+        .base_url(server.url())
+        .retry(fast_retries().max_retry_after(Duration::from_millis(10)))
+        .build();
+
+    let start = Instant::now();
+    client.schools().await.expect("Second attempt should work");
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    failing.assert();
+    working.assert();
+}
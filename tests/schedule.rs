@@ -1,5 +1,10 @@
+use chrono::{Datelike, NaiveDate};
 use mockito::Server;
-use schoolsoft::{deserializers::Deserializer, schedule::Schedule};
+use schoolsoft::{
+    clock::{Clock, FixedClock},
+    deserializers::Deserializer,
+    schedule::Schedule,
+};
 
 use crate::mock::{basic_user_with_token, get};
 
@@ -25,7 +30,7 @@ async fn request() {
         None,
     );
 
-    let mut user = basic_user_with_token(&server.url());
+    let user = basic_user_with_token(&server.url());
 
     let res = user.get_schedule().await;
 
@@ -33,3 +38,49 @@ async fn request() {
 
     res.expect("Getting schedule should be successful");
 }
+
+/// The clock decides which year the weeks of the schedule belong to
+#[test]
+#[allow(deprecated)]
+fn year_from_clock() {
+    let data = include_str!("../hurl/output/schedule.json");
+
+    let spring = FixedClock::new(NaiveDate::from_ymd(2024, 4, 1).and_hms(12, 0, 0));
+    let schedule = Schedule::deserialize_with_clock(data, &spring).unwrap();
+    assert_eq!(schedule.weeks[9].monday.date.year(), 2024);
+    assert_eq!(schedule.weeks[39].monday.date.year(), 2023);
+
+    let autumn = FixedClock::new(NaiveDate::from_ymd(2024, 9, 1).and_hms(12, 0, 0));
+    let schedule = Schedule::deserialize_with_clock(data, &autumn).unwrap();
+    assert_eq!(schedule.weeks[9].monday.date.year(), 2025);
+    assert_eq!(schedule.weeks[39].monday.date.year(), 2024);
+
+    assert_eq!(
+        schedule.today(&autumn).unwrap().date,
+        NaiveDate::from_ymd(2024, 9, 1)
+    );
+}
+
+/// Finding the next lesson should only depend on the clock
+#[test]
+#[allow(deprecated)]
+fn next_lesson() {
+    let data = include_str!("../hurl/output/schedule.json");
+
+    // Tuesday of week 12
+    let clock = FixedClock::new(NaiveDate::from_ymd(2024, 3, 19).and_hms(12, 0, 0));
+    let schedule = Schedule::deserialize_with_clock(data, &clock).unwrap();
+
+    let (day, lesson) = schedule.next_lesson(&clock).expect("There should be a next lesson");
+
+    assert!(day.date.and_time(lesson.start) >= clock.now());
+
+    // No lesson later today should have been skipped
+    let today = schedule.today(&clock).unwrap();
+    if day.date != today.date {
+        assert!(today
+            .lessons
+            .iter()
+            .all(|lesson| today.date.and_time(lesson.start) < clock.now()));
+    }
+}
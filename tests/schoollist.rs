@@ -1,4 +1,4 @@
-use schoolsoft::ClientBuilder;
+use schoolsoft::{types::LoginMethod, ClientBuilder};
 use tokio::test;
 
 #[test]
@@ -36,9 +36,18 @@ async fn single() {
         schools[0].url,
         "https://sms.schoolsoft.se/carlwahren/".to_string()
     );
-    let empty: Vec<u8> = Vec::new();
-    assert_eq!(schools[0].login_methods.student, vec![0, 1, 2, 3, 4]);
-    assert_eq!(schools[0].login_methods.parent, vec![0]);
+    let empty: Vec<LoginMethod> = Vec::new();
+    assert_eq!(
+        schools[0].login_methods.student,
+        vec![
+            LoginMethod::Other(0),
+            LoginMethod::Other(1),
+            LoginMethod::Other(2),
+            LoginMethod::Other(3),
+            LoginMethod::App
+        ]
+    );
+    assert_eq!(schools[0].login_methods.parent, vec![LoginMethod::Other(0)]);
     assert_eq!(schools[0].login_methods.teacher, empty);
 
     mock.assert();
@@ -0,0 +1,83 @@
+use schoolsoft::{
+    session::{MemorySessionStore, SessionStore},
+    ClientBuilder,
+};
+
+use crate::mock::{get_with_token_and_login, login_mock};
+
+mod mock;
+
+/// Save a logged in user and restore it in a new client without logging in again
+#[tokio::test]
+async fn save_and_restore() {
+    let mut server = mockito::Server::new();
+    let login = login_mock(&mut server, None);
+
+    let store = MemorySessionStore::new();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    client
+        .user
+        .as_ref()
+        .expect("User should be set after login")
+        .save_session(&store)
+        .expect("Saving should work");
+
+    login.assert();
+
+    let mut restored = ClientBuilder::new().base_url(server.url()).build();
+    assert!(restored
+        .restore_session(&store)
+        .expect("Restoring should work"));
+
+    let user = restored.user.expect("User should be set after restoring");
+    let original = client.user.unwrap();
+
+    assert_eq!(user.name, original.name);
+    assert_eq!(user.app_key(), original.app_key());
+    assert_eq!(user.school_url, original.school_url);
+    assert_eq!(user.orgs, original.orgs);
+
+    // Restoring should not have made any requests
+    login.expect(1).assert();
+}
+
+/// A restored user should refresh its token like normal when making requests
+#[tokio::test]
+async fn restored_user_refreshes_token() {
+    let mut server = mockito::Server::new();
+    let (login, token, lunch) = get_with_token_and_login(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+    );
+
+    let store = MemorySessionStore::new();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+    client.user.unwrap().save_session(&store).unwrap();
+
+    let mut restored = ClientBuilder::new().base_url(server.url()).build();
+    restored.restore_session(&store).unwrap();
+
+    let user = restored.user.unwrap();
+    user.get_lunch().await.expect("Getting lunch should work");
+
+    // The refreshed token can be saved for next time
+    user.save_session(&store).unwrap();
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.token.unwrap().token.expose(), "one_of_those_tokens");
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+}
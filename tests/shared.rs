@@ -0,0 +1,60 @@
+use schoolsoft::user::User;
+
+use crate::mock::{basic_user, get, token_mock};
+
+mod mock;
+
+#[test]
+fn user_is_send_and_sync() {
+    fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+    assert_shareable::<User>();
+}
+
+/// Clones share the same token
+#[tokio::test]
+async fn clones_share_token() {
+    let mut server = mockito::Server::new();
+    let token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+
+    let user = basic_user(&server.url());
+    let clone = user.clone();
+
+    user.smart_token().await.expect("Getting a token should work");
+
+    assert_eq!(clone.token().unwrap().token.expose(), "one_of_those_tokens");
+    clone.smart_token().await.expect("Token should be reused");
+
+    token.expect(1).assert();
+}
+
+/// Many tasks needing a token at the same time should only result in one token request
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn single_flight_refresh() {
+    let mut server = mockito::Server::new_async().await;
+
+    let token = token_mock(&mut server, "one_of_those_tokens", Some("2099-01-01 00:00:00.0"));
+    let lunch = get(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+        None,
+    );
+
+    let user = basic_user(&server.url());
+
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let user = user.clone();
+            tokio::spawn(async move { user.get_lunch().await })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await
+            .expect("Task should not panic")
+            .expect("Getting lunch should work");
+    }
+
+    token.expect(1).assert();
+    lunch.expect(8).assert();
+}
@@ -1,9 +1,13 @@
 #![allow(deprecated)]
 use chrono::{Duration, NaiveDate};
-use schoolsoft::types::error::{RequestError, TokenError};
+use schoolsoft::{
+    clock::FixedClock,
+    types::error::{RequestError, TokenError},
+    ClientBuilder,
+};
 use tokio::test;
 
-use crate::mock::{basic_user, token_mock};
+use crate::mock::{basic_user, login_mock, token_mock};
 
 mod mock;
 
@@ -28,7 +32,7 @@ async fn ok() {
         .await
         .expect("Retrieving a token should work");
 
-    assert_eq!(token.token, target_token);
+    assert_eq!(token.token.expose(), target_token);
     assert_eq!(token.expires, expiration_date);
 
     mock.assert();
@@ -50,10 +54,41 @@ async fn unauthorized() {
         .await
         .expect_err("This should not work");
 
-    if let TokenError::RequestError(RequestError::Unauthorized) = result {
+    if let TokenError::RequestError(RequestError::Unauthorized(_)) = result {
     } else {
         panic!("Expected Unauthorized, got {:?}", result)
     }
 
     mock.assert();
 }
+
+/// Users created by a client should check token expiry against the clients clock
+#[test]
+async fn client_clock() {
+    let mut server = mockito::Server::new();
+    let login = login_mock(&mut server, None);
+    let token = token_mock(&mut server, "123notreal", None);
+
+    let clock = FixedClock::new(NaiveDate::from_ymd(2024, 2, 12).and_hms(16, 22, 23));
+
+    let mut client = ClientBuilder::new()
+        .base_url(server.url())
+        .clock(clock)
+        .build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should work");
+
+    let user = client.user.unwrap();
+
+    // The token expires an hour after the clock, so it should only be retrieved once
+    let first = user.smart_token().await.expect("Getting a token should work");
+    let second = user.smart_token().await.expect("Getting a token should work");
+
+    assert_eq!(first, second);
+    assert_eq!(first.expires_in(), Duration::hours(1) + Duration::milliseconds(714));
+
+    login.assert();
+    token.expect(1).assert();
+}
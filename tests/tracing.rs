@@ -0,0 +1,136 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use schoolsoft::ClientBuilder;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+use crate::mock::{get_with_token_and_login, login_mock, token_mock};
+
+mod mock;
+
+/// Writes the name and every field of all spans and events to a string
+#[derive(Debug, Default, Clone)]
+struct Capture {
+    output: Arc<Mutex<String>>,
+    ids: Arc<AtomicU64>,
+}
+
+impl Visit for Capture {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let mut output = self.output.lock().unwrap();
+        output.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.output
+            .lock()
+            .unwrap()
+            .push_str(&format!("\n{}:", span.metadata().name()));
+        span.record(&mut self.clone());
+
+        Id::from_u64(self.ids.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _span: &Id, values: &Record<'_>) {
+        values.record(&mut self.clone());
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.output.lock().unwrap().push_str("\nevent:");
+        event.record(&mut self.clone());
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// Spans are recorded with useful fields but never include passwords, app keys or tokens
+#[tokio::test]
+async fn spans_without_secrets() {
+    let mut server = mockito::Server::new();
+    let (login, token, lunch) = get_with_token_and_login(
+        &mut server,
+        "api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+    );
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_user", "very_secret_password", "mock_school")
+        .await
+        .expect("Failed to login");
+    let user = client.user.expect("User should be set after login");
+    user.get_lunch().await.expect("Failed to get lunch");
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+
+    let output = capture.output.lock().unwrap().clone();
+
+    for span in ["login:", "token:", "lunch:", "request:", "parse:"] {
+        assert!(output.contains(span), "Missing {span} in {output}");
+    }
+    assert!(output.contains("status=200"), "Missing status in {output}");
+    assert!(
+        output.contains("latency_ms="),
+        "Missing latency in {output}"
+    );
+
+    for secret in ["very_secret_password", "123notreal", "one_of_those_tokens"] {
+        assert!(!output.contains(secret), "Found {secret} in {output}");
+    }
+}
+
+/// Failing to parse is recorded without the response
+#[tokio::test]
+async fn parse_error() {
+    let mut server = mockito::Server::new();
+    let _login = login_mock(&mut server, None);
+    let _token = token_mock(&mut server, "\"one_of_those_tokens", None);
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_user", "very_secret_password", "mock_school")
+        .await
+        .expect("Failed to login");
+    let user = client.user.expect("User should be set after login");
+
+    user.get_token().await.expect_err("Token should not parse");
+
+    let output = capture.output.lock().unwrap().clone();
+    assert!(
+        output.contains("failed to parse response"),
+        "Missing error in {output}"
+    );
+    assert!(
+        !output.contains("one_of_those_tokens"),
+        "Found token in {output}"
+    );
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::StatusCode;
+use schoolsoft::{
+    clock::{Clock, FixedClock},
+    http::{Request, Response},
+    retry::RetryPolicy,
+    transport::{BoxFuture, HttpTransport},
+    types::error::{LunchMenuError, RequestError, SchoolListingError},
+    ClientBuilder,
+};
+
+use crate::mock::{basic_user_with_token, mock_clock};
+
+mod mock;
+
+/// Answers requests from a map of urls to bodies without any network
+#[derive(Debug, Default)]
+struct Fake {
+    routes: HashMap<String, String>,
+    sent: Mutex<Vec<Request>>,
+}
+
+/// Error returned by [`Fake`] for urls it doesn't know
+#[derive(Debug, thiserror::Error)]
+#[error("no route for {0}")]
+struct NoRoute(String);
+
+impl Fake {
+    fn route(mut self, url: &str, body: &str) -> Self {
+        self.routes.insert(url.to_string(), body.to_string());
+        self
+    }
+}
+
+impl HttpTransport for Fake {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, RequestError>> {
+        Box::pin(async move {
+            self.sent.lock().unwrap().push(request.clone());
+
+            let body = self.routes.get(&request.url).ok_or_else(|| {
+                RequestError::TransportError(Box::new(NoRoute(request.url.clone())))
+            })?;
+
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: Vec::new(),
+                body: body.clone(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn schools() {
+    let fake = Arc::new(Fake::default().route(
+        "http://fake/rest/app/schoollist/prod",
+        include_str!("../hurl/output/school-list.json"),
+    ));
+
+    let client = ClientBuilder::new()
+        .base_url("http://fake".to_string())
+        .transport(fake.clone())
+        .build();
+
+    let schools = client.schools().await.expect("Failed to get schools");
+    assert_eq!(schools.len(), 3060);
+    assert_eq!(fake.sent.lock().unwrap().len(), 1);
+}
+
+/// Users created by the client send requests through the same transport
+#[tokio::test]
+async fn users_share_transport() {
+    let fake = Arc::new(Fake::default().route(
+        "http://fake/mock_school/api/lunchmenus/student/1",
+        include_str!("../hurl/output/lunch.json"),
+    ));
+
+    let client = ClientBuilder::new()
+        .base_url("http://fake".to_string())
+        .clock(FixedClock::new(mock_clock().now()))
+        .transport(fake.clone())
+        .build();
+
+    let user = client.adopt(basic_user_with_token("http://fake"));
+    user.get_lunch().await.expect("Failed to get lunch");
+
+    let sent = fake.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].header_value("token"), Some("one_of_those_tokens"));
+}
+
+/// Transport errors are reported and retried like any other failure to send a request
+#[tokio::test]
+async fn transport_error() {
+    let fake = Arc::new(Fake::default());
+
+    let client = ClientBuilder::new()
+        .base_url("http://fake".to_string())
+        .retry(RetryPolicy::new().backoff(Default::default(), Default::default()))
+        .transport(fake.clone())
+        .build();
+
+    match client.schools().await {
+        Err(SchoolListingError::RequestError(RequestError::TransportError(_))) => (),
+        other => panic!("Expected TransportError, got {:?}", other),
+    }
+    assert_eq!(fake.sent.lock().unwrap().len(), 3);
+
+    let user = client.adopt(basic_user_with_token("http://fake"));
+    match user.get_lunch().await {
+        Err(LunchMenuError::TokenError(_)) => (),
+        other => panic!("Expected TokenError, got {:?}", other),
+    }
+}
+
+/// The reqwest transport sends the app key, token and body it copies into zeroed buffers
+#[tokio::test]
+async fn reqwest_sends_secrets() {
+    let mut server = mockito::Server::new();
+
+    let token = server
+        .mock("POST", "/mock_school/rest/app/token")
+        .match_header("appkey", "123notreal")
+        .with_status(200)
+        .with_body(r#"{"expiryDate":"2024-02-12 17:22:23.714","token":"one_of_those_tokens"}"#)
+        .create();
+    let lunch = server
+        .mock("GET", "/mock_school/api/lunchmenus/student/1")
+        .match_header("token", "one_of_those_tokens")
+        .with_status(200)
+        .with_body(include_str!("../hurl/output/lunch.json"))
+        .create();
+    let login = server
+        .mock("POST", "/mock_school/rest/app/login")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "verification".into(),
+            "mock_password".into(),
+        ))
+        .with_status(200)
+        .with_body(crate::mock::login_body(
+            &server.url(),
+            "mock_school",
+            schoolsoft::user::UserType::Student,
+        ))
+        .create();
+
+    let mut client = ClientBuilder::new().base_url(server.url()).build();
+    client
+        .login("mock_username", "mock_password", "mock_school")
+        .await
+        .expect("Login should be successful");
+
+    let user = client.user.expect("User should be set after login");
+    let user = user.with_clock(mock_clock());
+    user.get_lunch()
+        .await
+        .expect("The lunch menu should be sent");
+
+    login.assert();
+    token.assert();
+    lunch.assert();
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use mockito::Matcher;
+use schoolsoft::{
+    cassette::RecordingTransport,
+    types::error::{RequestError, WebSessionError},
+    web::WebSession,
+};
+
+use crate::mock::basic_user_with_token;
+
+mod mock;
+
+/// Login with the token login url and use the cookies to get a page
+#[tokio::test]
+async fn web_session() {
+    let mut server = mockito::Server::new();
+
+    let login = server
+        .mock("GET", "/mock_school/jsp/app/TokenLogin.jsp")
+        .match_query(Matcher::UrlEncoded(
+            "token".into(),
+            "one_of_those_tokens".into(),
+        ))
+        .with_status(302)
+        .with_header("Set-Cookie", "JSESSIONID=mock_session; Path=/mock_school")
+        .with_header("Location", "/mock_school/jsp/student/right_student_startpage.jsp")
+        .create();
+
+    let start = server
+        .mock("GET", "/mock_school/jsp/student/right_student_startpage.jsp")
+        .match_header("cookie", "JSESSIONID=mock_session")
+        .with_body("<html>start</html>")
+        .expect(1)
+        .create();
+
+    let page = server
+        .mock("GET", "/mock_school/jsp/student/right_student_news.jsp")
+        .match_header("cookie", "JSESSIONID=mock_session")
+        .with_body("<html>news</html>")
+        .create();
+
+    let user = basic_user_with_token(&server.url());
+    let session = user.web_session().await.expect("Logging in should work");
+
+    let body = session
+        .get("/jsp/student/right_student_news.jsp")
+        .await
+        .expect("Getting a page should work");
+    assert_eq!(body, "<html>news</html>");
+
+    login.assert();
+    start.assert();
+    page.assert();
+}
+
+/// Pages are requested from the host the token login url is on, where the cookies were set
+#[tokio::test]
+async fn token_login_on_other_host() {
+    let mut api = mockito::Server::new();
+    let mut website = mockito::Server::new();
+
+    let login = website
+        .mock("GET", "/mock_school/jsp/app/TokenLogin.jsp")
+        .match_query(Matcher::Any)
+        .with_status(302)
+        .with_header("Set-Cookie", "JSESSIONID=mock_session; Path=/mock_school")
+        .with_header("Location", "/mock_school/jsp/student/right_student_startpage.jsp")
+        .create();
+    let start = website
+        .mock("GET", "/mock_school/jsp/student/right_student_startpage.jsp")
+        .with_body("<html>start</html>")
+        .create();
+    let page = website
+        .mock("GET", "/mock_school/jsp/student/right_student_news.jsp")
+        .match_header("cookie", "JSESSIONID=mock_session")
+        .with_body("<html>news</html>")
+        .create();
+    let wrong_host = api.mock("GET", Matcher::Any).expect(0).create();
+
+    let mut user = basic_user_with_token(&api.url());
+    user.orgs[0].token_login = format!(
+        "{}/mock_school/jsp/app/TokenLogin.jsp?token=TOKEN_PLACEHOLDER&orgid=1",
+        website.url()
+    );
+
+    let session = user.web_session().await.expect("Logging in should work");
+    assert_eq!(
+        session.school_url().as_str(),
+        format!("{}/mock_school", website.url())
+    );
+
+    let body = session
+        .get("jsp/student/right_student_news.jsp")
+        .await
+        .expect("Getting a page should work");
+    assert_eq!(body, "<html>news</html>");
+
+    login.assert();
+    start.assert();
+    page.assert();
+    wrong_host.assert();
+}
+
+/// Pages are sent through the transport given to the session
+#[tokio::test]
+async fn web_session_with_transport() {
+    let mut server = mockito::Server::new();
+
+    let login = server
+        .mock("GET", "/mock_school/jsp/app/TokenLogin.jsp")
+        .match_query(Matcher::Any)
+        .with_body("<html>start</html>")
+        .create();
+
+    let page = server
+        .mock("GET", "/mock_school/jsp/student/right_student_news.jsp")
+        .with_body("<html>news</html>")
+        .create();
+
+    let user = basic_user_with_token(&server.url());
+    let transport = WebSession::transport(&user.device, &Default::default())
+        .expect("Creating the transport should work");
+    let recorder = Arc::new(RecordingTransport::new(transport));
+
+    let session = user
+        .web_session_with_transport(user.org().unwrap(), recorder.clone())
+        .await
+        .expect("Logging in should work");
+    session
+        .get("jsp/student/right_student_news.jsp")
+        .await
+        .expect("Getting a page should work");
+
+    let cassette = recorder.cassette();
+    assert_eq!(cassette.interactions.len(), 2);
+    assert_eq!(cassette.interactions[1].response.body, "<html>news</html>");
+
+    // The token in the login url is never recorded
+    let data = serde_json::to_string(&cassette).expect("Failed to serialize cassette");
+    assert!(!data.contains("one_of_those_tokens"));
+    assert!(data.contains("token=%3Credacted%3E"));
+
+    login.assert();
+    page.assert();
+}
+
+/// A rejected token login is returned as an error
+#[tokio::test]
+async fn rejected() {
+    let mut server = mockito::Server::new();
+
+    let login = server
+        .mock("GET", "/mock_school/jsp/app/TokenLogin.jsp")
+        .match_query(Matcher::Any)
+        .with_status(401)
+        .create();
+
+    let user = basic_user_with_token(&server.url());
+
+    match user.web_session().await {
+        Err(WebSessionError::RequestError(RequestError::Unauthorized(_))) => (),
+        other => panic!("Expected Unauthorized, got {:?}", other),
+    }
+
+    login.assert();
+}